@@ -1,7 +1,8 @@
 use std::path::{Path, PathBuf};
 use std::collections::{HashSet, VecDeque};
 
-use crate::graph::{DependencyGraph, FileId};
+use crate::graph::{DependencyGraph, EdgeInfo, FileId};
+use crate::parser::{ImportType, SourceLocation};
 use crate::git::ChangeSet;
 
 #[derive(Debug, Default)]
@@ -13,6 +14,39 @@ pub struct AffectedResult {
 #[derive(Debug, Clone)]
 pub struct DependencyPath {
     pub chain: Vec<PathBuf>,
+    /// One entry per hop in `chain`, naming the import that links the two files
+    pub steps: Vec<ImportStep>,
+}
+
+/// The import statement behind one hop of a [`DependencyPath`].
+#[derive(Debug, Clone)]
+pub struct ImportStep {
+    /// File containing the import
+    pub importer: PathBuf,
+    pub location: Option<SourceLocation>,
+    /// Names the importer uses from the next file
+    pub symbols: Vec<String>,
+    /// Barrel file the import went through, when it was expanded
+    pub via_barrel: Option<PathBuf>,
+}
+
+impl ImportStep {
+    /// `src/a.ts:12 `import { calc } from './utils'` uses calc via src/utils/index.ts`,
+    /// with paths relative to `root` when possible.
+    pub fn format(&self, root: &Path) -> String {
+        let path = self.importer.strip_prefix(root).unwrap_or(&self.importer);
+        let mut line = match &self.location {
+            Some(loc) => format!("{}:{} `{}`", path.display(), loc.line, loc.text),
+            None => path.display().to_string(),
+        };
+        if !self.symbols.is_empty() {
+            line.push_str(&format!(" uses {}", self.symbols.join(", ")));
+        }
+        if let Some(barrel) = &self.via_barrel {
+            line.push_str(&format!(" via {}", barrel.strip_prefix(root).unwrap_or(barrel).display()));
+        }
+        line
+    }
 }
 
 impl DependencyPath {
@@ -27,26 +61,84 @@ impl DependencyPath {
 
 pub struct AffectedTestFinder<'a> {
     graph: &'a DependencyGraph,
+    ignore_type_edges: bool,
+    ignored_kinds: Vec<ImportType>,
+    parse_errors_affect_package: bool,
 }
 
 impl<'a> AffectedTestFinder<'a> {
     pub fn new(graph: &'a DependencyGraph) -> Self {
-        Self { graph }
+        Self { graph, ignore_type_edges: false, ignored_kinds: Vec::new(), parse_errors_affect_package: false }
+    }
+
+    /// Stop changes from propagating through type-only imports.
+    pub fn ignore_type_edges(mut self, ignore: bool) -> Self {
+        self.ignore_type_edges = ignore;
+        self
+    }
+
+    /// Stop changes from propagating through imports of these kinds, e.g.
+    /// [`ImportType::DynamicImport`] for lazily loaded routes. An edge created by
+    /// several imports is still followed if any of them is of another kind.
+    pub fn ignore_import_kinds(mut self, kinds: &[ImportType]) -> Self {
+        self.ignored_kinds = kinds.to_vec();
+        self
+    }
+
+    /// Files parsed with errors may be missing edges; with this set, any change in
+    /// such a file's package (nearest `package.json`) also marks the file affected.
+    pub fn parse_errors_affect_package(mut self, enabled: bool) -> Self {
+        self.parse_errors_affect_package = enabled;
+        self
+    }
+
+    fn follows(&self, edge: &EdgeInfo) -> bool {
+        if self.ignore_type_edges && edge.type_only {
+            return false;
+        }
+        edge.kinds.is_empty() || edge.kinds.iter().any(|kind| !self.ignored_kinds.contains(kind))
+    }
+
+    /// Files with parse errors whose package contains one of `changed_paths`.
+    /// A file outside any package is affected by every change.
+    fn broken_files_affected_by(&self, changed_paths: &[PathBuf]) -> Vec<FileId> {
+        self.graph
+            .get_parse_diagnostics()
+            .into_iter()
+            .filter(|(path, _)| match package_root(path, self.graph.root()) {
+                Some(package) => changed_paths.iter().any(|c| c.starts_with(&package)),
+                None => true,
+            })
+            .filter_map(|(path, _)| self.graph.get_file_id(&path))
+            .collect()
+    }
+
+    /// Graph nodes of the changed files and of the external packages whose
+    /// versions changed.
+    fn changed_ids(&self, changes: &ChangeSet) -> Vec<FileId> {
+        changes
+            .all_changed()
+            .iter()
+            .filter_map(|p| self.graph.get_file_id(p))
+            .chain(changes.packages.iter().filter_map(|name| self.graph.get_package_id(name)))
+            .collect()
     }
 
     pub fn find_affected(&self, changes: &ChangeSet) -> AffectedResult {
         let changed_paths = changes.all_changed();
         
-        let changed_ids: Vec<_> = changed_paths
-            .iter()
-            .filter_map(|p| self.graph.get_file_id(p))
-            .collect();
+        let mut changed_ids = self.changed_ids(changes);
+
+        if self.parse_errors_affect_package && !changed_paths.is_empty() {
+            changed_ids.extend(self.broken_files_affected_by(&changed_paths));
+        }
 
         if changed_ids.is_empty() {
             return AffectedResult::default();
         }
 
-        let affected_ids = self.graph.get_transitive_dependents(&changed_ids);
+        let affected_ids = self.graph
+            .get_transitive_dependents_filtered(&changed_ids, |e| self.follows(e));
 
         let mut tests = Vec::new();
         let mut sources = Vec::new();
@@ -60,11 +152,11 @@ impl<'a> AffectedTestFinder<'a> {
                 seen_paths.insert(node.path.clone());
 
                 if node.is_test {
-                    tests.push(node.path.clone());
+                    tests.push(self.graph.absolute_path(&node.path));
                 } else {
                     let path_str = node.path.to_string_lossy();
                     if !path_str.contains("node_modules") {
-                        sources.push(node.path.clone());
+                        sources.push(self.graph.absolute_path(&node.path));
                     }
                 }
             }
@@ -79,11 +171,7 @@ impl<'a> AffectedTestFinder<'a> {
     pub fn find_why(&self, test_path: &Path, changes: &ChangeSet) -> Option<DependencyPath> {
         let test_id = self.graph.get_file_id(test_path)?;
         
-        let changed_ids: HashSet<_> = changes
-            .all_changed()
-            .iter()
-            .filter_map(|p| self.graph.get_file_id(p))
-            .collect();
+        let changed_ids: HashSet<_> = self.changed_ids(changes).into_iter().collect();
 
         if changed_ids.is_empty() {
             return None;
@@ -97,14 +185,10 @@ impl<'a> AffectedTestFinder<'a> {
 
         while let Some((current, path)) = queue.pop_front() {
             if changed_ids.contains(&current) {
-                let chain: Vec<PathBuf> = path
-                    .iter()
-                    .filter_map(|&id| self.graph.get_file_path(id).map(|p| p.to_path_buf()))
-                    .collect();
-                return Some(DependencyPath { chain });
+                return Some(self.build_path(&path, true));
             }
 
-            for dep in self.graph.get_dependencies(current) {
+            for dep in self.graph.get_dependencies_filtered(current, |e| self.follows(e)) {
                 if !visited.contains(&dep) {
                     visited.insert(dep);
                     let mut new_path = path.clone();
@@ -123,11 +207,7 @@ impl<'a> AffectedTestFinder<'a> {
             None => return vec![],
         };
 
-        let changed_ids: HashSet<_> = changes
-            .all_changed()
-            .iter()
-            .filter_map(|p| self.graph.get_file_id(p))
-            .collect();
+        let changed_ids: HashSet<_> = self.changed_ids(changes).into_iter().collect();
 
         if changed_ids.is_empty() {
             return vec![];
@@ -153,14 +233,10 @@ impl<'a> AffectedTestFinder<'a> {
 
         while let Some((current, path)) = queue.pop_front() {
             if current == to {
-                let chain: Vec<PathBuf> = path
-                    .iter()
-                    .filter_map(|&id| self.graph.get_file_path(id).map(|p| p.to_path_buf()))
-                    .collect();
-                return Some(DependencyPath { chain });
+                return Some(self.build_path(&path, false));
             }
 
-            for dep in self.graph.get_dependents(current) {
+            for dep in self.graph.get_dependents_filtered(current, |e| self.follows(e)) {
                 if !visited.contains(&dep) {
                     visited.insert(dep);
                     let mut new_path = path.clone();
@@ -173,14 +249,48 @@ impl<'a> AffectedTestFinder<'a> {
         None
     }
 
+    /// Turns a chain of file ids into a [`DependencyPath`]. `importers_first` is
+    /// true when each file imports the next one (test → change) and false when
+    /// each file is imported by the next one (change → test).
+    fn build_path(&self, ids: &[FileId], importers_first: bool) -> DependencyPath {
+        let chain: Vec<PathBuf> = ids
+            .iter()
+            .filter_map(|&id| self.graph.get_file_path(id))
+            .collect();
+
+        let steps = ids
+            .windows(2)
+            .map(|pair| {
+                let (importer, imported) = if importers_first { (pair[0], pair[1]) } else { (pair[1], pair[0]) };
+                let edge = self.graph.get_edge_info(importer, imported).cloned().unwrap_or_default();
+                ImportStep {
+                    importer: self.graph.get_file_path(importer).unwrap_or_default(),
+                    location: edge.location,
+                    symbols: edge.symbols,
+                    via_barrel: edge.via_barrel.map(|barrel| self.graph.absolute_path(&barrel)),
+                }
+            })
+            .collect();
+
+        DependencyPath { chain, steps }
+    }
+
     pub fn get_totals(&self) -> (usize, usize) {
-        let all_files = self.graph.get_all_files();
         let test_count = self.graph.get_test_files().len();
-        let source_count = all_files.len() - test_count;
+        let source_count = self.graph.file_count() - test_count;
         (test_count, source_count)
     }
 }
 
+/// Directory of the nearest `package.json` above `file`, within `root`.
+fn package_root(file: &Path, root: &Path) -> Option<PathBuf> {
+    file.ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(root))
+        .find(|dir| dir.join("package.json").is_file())
+        .map(Path::to_path_buf)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,7 +338,7 @@ mod tests {
         
         assert_eq!(result.tests.len(), 1);
         assert!(result.tests[0].to_string_lossy().contains("test.spec.ts"));
-        assert!(result.sources.len() >= 1);
+        assert!(!result.sources.is_empty());
     }
 
     #[test]
@@ -245,4 +355,173 @@ mod tests {
         
         assert!(result.tests.is_empty());
     }
+
+    #[test]
+    fn test_find_affected_by_package_bump() {
+        let mut graph = create_test_graph();
+        let utils = graph.get_file_id(Path::new("/src/utils.ts")).unwrap();
+        let other_test = graph.add_file(PathBuf::from("/src/other.spec.ts"), true);
+        let lodash = graph.add_package("lodash");
+        let react = graph.add_package("react");
+        graph.add_dependency(utils, lodash);
+        graph.add_dependency(other_test, react);
+
+        let finder = AffectedTestFinder::new(&graph);
+        let changes = ChangeSet { packages: vec!["lodash".to_string()], ..Default::default() };
+
+        let result = finder.find_affected(&changes);
+
+        assert_eq!(result.tests, vec![PathBuf::from("/src/test.spec.ts")]);
+        assert_eq!(result.sources, vec![PathBuf::from("/src/component.ts"), PathBuf::from("/src/utils.ts")]);
+        assert_eq!(finder.get_totals(), (2, 2));
+
+        let why = finder.find_why(Path::new("/src/test.spec.ts"), &changes).unwrap();
+        assert_eq!(why.chain.last().unwrap(), Path::new("node_modules/lodash"));
+    }
+
+    #[test]
+    fn test_ignore_type_edges() {
+        let mut graph = DependencyGraph::new();
+        let types = graph.add_file(PathBuf::from("/src/types.ts"), false);
+        let test = graph.add_file(PathBuf::from("/src/types.spec.ts"), true);
+        graph.add_dependency_with_info(test, types, EdgeInfo { type_only: true, ..Default::default() });
+
+        let changes = ChangeSet {
+            modified: vec![PathBuf::from("/src/types.ts")],
+            ..Default::default()
+        };
+
+        let result = AffectedTestFinder::new(&graph).find_affected(&changes);
+        assert_eq!(result.tests.len(), 1);
+
+        let finder = AffectedTestFinder::new(&graph).ignore_type_edges(true);
+        assert!(finder.find_affected(&changes).tests.is_empty());
+        assert!(finder.find_why(Path::new("/src/types.spec.ts"), &changes).is_none());
+    }
+
+    #[test]
+    fn test_find_why_reports_import_locations() {
+        let mut graph = DependencyGraph::new();
+        let calc = graph.add_file(PathBuf::from("/src/calc.ts"), false);
+        let test = graph.add_file(PathBuf::from("/src/calc.spec.ts"), true);
+        graph.add_dependency_with_info(test, calc, EdgeInfo {
+            type_only: false,
+            location: Some(SourceLocation {
+                line: 12,
+                column: 1,
+                text: "import { calc } from './calc';".to_string(),
+            }),
+            ..Default::default()
+        });
+
+        let changes = ChangeSet {
+            modified: vec![PathBuf::from("/src/calc.ts")],
+            ..Default::default()
+        };
+        let finder = AffectedTestFinder::new(&graph);
+
+        let path = finder.find_why(Path::new("/src/calc.spec.ts"), &changes).unwrap();
+        assert_eq!(path.steps.len(), 1);
+        assert_eq!(path.steps[0].format(Path::new("/")), "src/calc.spec.ts:12 `import { calc } from './calc';`");
+
+        let all = finder.find_all_paths_to_test(Path::new("/src/calc.spec.ts"), &changes);
+        assert_eq!(all[0].steps[0].importer, PathBuf::from("/src/calc.spec.ts"));
+    }
+
+    #[test]
+    fn test_ignore_import_kinds() {
+        let mut graph = DependencyGraph::new();
+        let page = graph.add_file(PathBuf::from("/src/Page.tsx"), false);
+        let router = graph.add_file(PathBuf::from("/src/router.ts"), false);
+        let test = graph.add_file(PathBuf::from("/src/router.spec.ts"), true);
+        let lazy = EdgeInfo { kinds: vec![ImportType::DynamicImport], ..Default::default() };
+        graph.add_dependency_with_info(router, page, lazy.clone());
+        graph.add_dependency_with_info(test, router, EdgeInfo { kinds: vec![ImportType::StaticImport], ..Default::default() });
+
+        let changes = ChangeSet { modified: vec![PathBuf::from("/src/Page.tsx")], ..Default::default() };
+        let finder = AffectedTestFinder::new(&graph).ignore_import_kinds(&[ImportType::DynamicImport]);
+        assert!(finder.find_affected(&changes).tests.is_empty());
+
+        // A static import of the same file keeps the edge
+        graph.add_dependency_with_info(router, page, EdgeInfo { kinds: vec![ImportType::StaticImport], ..Default::default() });
+        assert_eq!(graph.get_edge_info(router, page).unwrap().kinds, vec![ImportType::StaticImport, ImportType::DynamicImport]);
+        let finder = AffectedTestFinder::new(&graph).ignore_import_kinds(&[ImportType::DynamicImport]);
+        assert_eq!(finder.find_affected(&changes).tests, vec![PathBuf::from("/src/router.spec.ts")]);
+    }
+
+    #[test]
+    fn test_find_why_names_symbols() {
+        let mut graph = DependencyGraph::new();
+        let sum = graph.add_file(PathBuf::from("/src/math/sum.ts"), false);
+        let test = graph.add_file(PathBuf::from("/src/calc.spec.ts"), true);
+        graph.add_dependency_with_info(test, sum, EdgeInfo {
+            location: Some(SourceLocation { line: 1, column: 1, text: "import { sum } from './math';".to_string() }),
+            symbols: vec!["sum".to_string()],
+            via_barrel: Some(PathBuf::from("/src/math/index.ts")),
+            ..Default::default()
+        });
+
+        let changes = ChangeSet { modified: vec![PathBuf::from("/src/math/sum.ts")], ..Default::default() };
+        let path = AffectedTestFinder::new(&graph).find_why(Path::new("/src/calc.spec.ts"), &changes).unwrap();
+        assert_eq!(
+            path.steps[0].format(Path::new("/")),
+            "src/calc.spec.ts:1 `import { sum } from './math';` uses sum via src/math/index.ts"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_affect_package() {
+        use crate::parser::{ParseDiagnostic, SourceLocation};
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let app = temp.path().join("packages/app");
+        let lib = temp.path().join("packages/lib");
+        std::fs::create_dir_all(&app).unwrap();
+        std::fs::create_dir_all(&lib).unwrap();
+        std::fs::write(app.join("package.json"), "{}").unwrap();
+        std::fs::write(lib.join("package.json"), "{}").unwrap();
+
+        let mut graph = DependencyGraph::new();
+        let broken = graph.add_file(app.join("broken.test.ts"), true);
+        graph.add_file(app.join("util.ts"), false);
+        graph.add_file(lib.join("index.ts"), false);
+        graph.get_file_node_mut(broken).unwrap().parse_errors = vec![ParseDiagnostic {
+            location: SourceLocation::default(),
+            message: "Unexpected token".to_string(),
+        }];
+
+        let same_package = ChangeSet { modified: vec![app.join("util.ts")], ..Default::default() };
+        let other_package = ChangeSet { modified: vec![lib.join("index.ts")], ..Default::default() };
+
+        let finder = AffectedTestFinder::new(&graph);
+        assert!(finder.find_affected(&same_package).tests.is_empty());
+
+        let finder = finder.parse_errors_affect_package(true);
+        assert_eq!(finder.find_affected(&same_package).tests, vec![app.join("broken.test.ts")]);
+        assert!(finder.find_affected(&other_package).tests.is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors_package_stops_at_root() {
+        use crate::parser::{ParseDiagnostic, SourceLocation};
+
+        // The package.json above the project root must not scope its files
+        let temp = tempfile::TempDir::new().unwrap();
+        let project = temp.path().join("web/src");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(temp.path().join("web/package.json"), "{}").unwrap();
+
+        let mut graph = DependencyGraph::with_root(&project);
+        let project = graph.root().to_path_buf();
+        let broken = graph.add_file(project.join("broken.test.ts"), true);
+        graph.get_file_node_mut(broken).unwrap().parse_errors = vec![ParseDiagnostic {
+            location: SourceLocation::default(),
+            message: "Unexpected token".to_string(),
+        }];
+
+        let outside = project.parent().unwrap().parent().unwrap().join("server/index.ts");
+        let changes = ChangeSet { modified: vec![outside], ..Default::default() };
+        let finder = AffectedTestFinder::new(&graph).parse_errors_affect_package(true);
+        assert_eq!(finder.find_affected(&changes).tests, vec![project.join("broken.test.ts")]);
+    }
 }
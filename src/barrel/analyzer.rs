@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use oxc_allocator::Allocator;
 use oxc_parser::Parser;
@@ -125,9 +125,18 @@ impl BarrelAnalyzer {
     }
 
     fn analyze_barrel_uncached(&self, barrel_path: &Path) -> HashMap<String, ExportSource> {
+        self.collect_exports(barrel_path, &mut HashSet::new())
+    }
+
+    /// `visited` holds the files already read through `export *`, so a cycle of
+    /// re-exports ends instead of recursing forever.
+    fn collect_exports(&self, barrel_path: &Path, visited: &mut HashSet<PathBuf>) -> HashMap<String, ExportSource> {
         let mut exports: HashMap<String, ExportSource> = HashMap::new();
         let barrel_path = std::fs::canonicalize(barrel_path).unwrap_or(barrel_path.to_path_buf());
-        
+        if !visited.insert(barrel_path.clone()) {
+            return exports;
+        }
+
         let content = match std::fs::read_to_string(&barrel_path) {
             Ok(c) => c,
             Err(_) => return exports,
@@ -176,7 +185,7 @@ impl BarrelAnalyzer {
                 }
                 Statement::ExportAllDeclaration(decl) => {
                     let source_path = self.resolve_path(barrel_dir, decl.source.value.as_str());
-                    let nested_exports = self.analyze_barrel_uncached(&source_path);
+                    let nested_exports = self.collect_exports(&source_path, visited);
                     
                     for (name, source) in nested_exports {
                         exports.insert(name, source);
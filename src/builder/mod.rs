@@ -1,13 +1,13 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use rayon::prelude::*;
-use ignore::WalkBuilder;
 use thiserror::Error;
+use xxhash_rust::xxh3::Xxh3;
 
 use crate::config::Config;
-use crate::graph::DependencyGraph;
-use crate::parser::{self};
-use crate::resolver::PathResolver;
+use crate::graph::{DependencyGraph, EdgeInfo, UnresolvedImport};
+use crate::parser::{self, ImportInfo, ImportType, ParseDiagnostic, ParsedFile, SourceLocation};
+use crate::resolver::{self, project_walker, ModuleMapper, PathResolver, ResolveError};
 use crate::barrel::BarrelAnalyzer;
 
 #[derive(Error, Debug)]
@@ -18,27 +18,91 @@ pub enum BuildError {
     GraphError(String),
 }
 
+/// Files besides the config that decide how imports resolve: tsconfig paths
+/// and `extends`, package `exports`/`imports`, workspaces and jest mappings.
+/// `tsconfig*.json` is matched separately so extended bases count too.
+const RESOLUTION_FILES: &[&str] = &["package.json", "pnpm-workspace.yaml", "jest.config.json"];
+
 pub struct GraphBuilder {
     root: PathBuf,
     config: Config,
     resolver: PathResolver,
+    /// Resolution files under the root, found once for the fingerprint and
+    /// the config notes.
+    resolution_files: Vec<PathBuf>,
+    workspace_packages: usize,
+    fingerprint: u64,
 }
 
 impl GraphBuilder {
     pub fn new(root: PathBuf, config: Config) -> Self {
         let mut resolver = PathResolver::new(root.clone());
+        resolver.add_extensions(&config.extensions);
+        resolver.set_conditions(&config.conditions);
+        resolver.set_platforms(&config.platforms);
+        resolver.set_ignore_patterns(&config.ignore_patterns);
+        let workspace_packages = resolver.load_workspaces();
+
+        let mut mapper = ModuleMapper::load(&root);
+        mapper.add_aliases(&root, &config.aliases);
+        resolver.set_module_mapper(mapper);
         
         let tsconfig_path = root.join("tsconfig.json");
         if tsconfig_path.exists() {
             let _ = resolver.load_tsconfig(&tsconfig_path);
         }
 
-        Self { root, config, resolver }
+        let resolution_files = find_resolution_files(&root);
+        let fingerprint = config_fingerprint(&root, &config, &resolution_files);
+        Self { root, config, resolver, resolution_files, workspace_packages, fingerprint }
+    }
+
+    /// Hash of the config and of every resolution file in the project. A graph
+    /// built under a different fingerprint may resolve imports differently.
+    pub fn config_fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    /// Setup problems behind unresolved imports: bare specifiers that look
+    /// like aliases in a project without any tsconfig.json to define them,
+    /// and nested packages without a workspace config to make them resolvable.
+    pub fn config_notes(&self, unresolved: &[(PathBuf, UnresolvedImport)]) -> Vec<String> {
+        let mut notes = Vec::new();
+
+        let bare = unresolved
+            .iter()
+            .filter(|(_, import)| !import.specifier.starts_with(['.', '/', '#']))
+            .count();
+        let has_tsconfig = self.resolution_files.iter().any(|path| path.ends_with("tsconfig.json"));
+        if bare > 0 && !has_tsconfig {
+            notes.push(format!(
+                "No tsconfig.json in the project: {} unresolved bare imports may be path aliases without `paths`",
+                bare
+            ));
+        }
+
+        if self.workspace_packages == 0 {
+            let nested_packages = self
+                .resolution_files
+                .iter()
+                .filter(|path| path.ends_with("package.json") && path.parent() != Some(self.root.as_path()))
+                .count();
+            if nested_packages > 0 {
+                notes.push(format!(
+                    "{} nested package.json files but no pnpm-workspace.yaml or package.json `workspaces`: imports of these packages only resolve when installed",
+                    nested_packages
+                ));
+            }
+        }
+
+        notes
     }
 
     pub fn build(&self) -> Result<DependencyGraph, BuildError> {
         let files = self.discover_files()?;
-        let graph = Arc::new(Mutex::new(DependencyGraph::new()));
+        let mut graph = DependencyGraph::with_root(&self.root);
+        graph.set_config_fingerprint(self.fingerprint);
+        let graph = Arc::new(Mutex::new(graph));
         let barrel_analyzer = Arc::new(Mutex::new(BarrelAnalyzer::new(self.root.clone())));
         
         {
@@ -51,53 +115,16 @@ impl GraphBuilder {
 
         let parse_results: Vec<_> = files
             .par_iter()
-            .filter_map(|file| {
-                match parser::parse_file(file) {
-                    Ok(imports) => Some((file.clone(), imports)),
-                    Err(e) => {
-                        eprintln!("Warning: Failed to parse {}: {}", file.display(), e);
-                        None
-                    }
-                }
-            })
+            .map(|file| (file.clone(), parse_recovering(file)))
             .collect();
 
         {
             let mut g = graph.lock().unwrap();
             let mut analyzer = barrel_analyzer.lock().unwrap();
             
-            for (file, imports) in parse_results {
-                let from_id = g.get_file_id(&file).unwrap();
-                
-                for import in imports {
-                    if let Ok(resolved) = self.resolver.resolve(&file, &import.source) {
-                        let canonical_resolved = std::fs::canonicalize(&resolved).unwrap_or(resolved.clone());
-                        
-                        // Check if this is a barrel import with specific named imports
-                        if self.config.expand_barrels && !import.named_imports.is_empty() && !import.is_namespace {
-                            // Try to expand barrel imports to actual sources
-                            if analyzer.check_and_cache_barrel(&canonical_resolved) {
-                                let actual_sources = analyzer.resolve_barrel_imports(
-                                    &canonical_resolved,
-                                    &import.named_imports,
-                                );
-                                
-                                // Add edges to actual source files instead of barrel
-                                for (_name, source_path) in actual_sources {
-                                    if let Some(to_id) = g.get_file_id(&source_path) {
-                                        g.add_dependency(from_id, to_id);
-                                    }
-                                }
-                                continue;
-                            }
-                        }
-                        
-                        // Fallback: add edge to resolved path (non-barrel or namespace import)
-                        if let Some(to_id) = g.get_file_id(&canonical_resolved) {
-                            g.add_dependency(from_id, to_id);
-                        }
-                    }
-                }
+            for (file, parsed) in parse_results {
+                record_parse_errors(&mut g, &file, parsed.errors);
+                self.link_imports(&mut g, &mut analyzer, &file, &parsed.imports);
             }
         }
 
@@ -129,6 +156,45 @@ impl GraphBuilder {
             return Ok(0);
         }
 
+        // Files whose imports may resolve differently now, relinked like a full build would
+        let mut relink: Vec<PathBuf> = Vec::new();
+        for path in &deleted_files {
+            if let Some(id) = graph.get_file_id(path) {
+                relink.extend(graph.get_dependents(id).into_iter().filter_map(|d| graph.get_file_path(d)));
+            }
+        }
+        if !new_files.is_empty() {
+            relink.extend(graph.get_unresolved_imports().into_iter().map(|(path, _)| path));
+            relink.extend(shadowed_importers(graph, &new_files));
+        }
+        let added_or_deleted: Vec<PathBuf> = new_files.iter().chain(&deleted_files).cloned().collect();
+        relink.extend(graph.get_expanding_importers(&added_or_deleted));
+
+        // A file that turned into a barrel expands its importers' names now
+        let mut analyzer = BarrelAnalyzer::new(self.root.clone());
+        if self.config.expand_barrels {
+            for path in &stale_files {
+                if let Some(id) = graph.get_file_id(path).filter(|_| analyzer.check_and_cache_barrel(path)) {
+                    relink.extend(graph.get_dependents(id).into_iter().filter_map(|d| graph.get_file_path(d)));
+                }
+            }
+        }
+
+        // Names imported through a barrel are looked up again when the barrel,
+        // or a file it re-exports from, changes or is relinked itself
+        let changed: Vec<_> = stale_files
+            .iter()
+            .chain(&deleted_files)
+            .chain(&relink)
+            .filter_map(|path| graph.get_file_id(path))
+            .collect();
+        let barrels: Vec<PathBuf> = graph
+            .get_transitive_dependents_filtered(&changed, |edge| edge.has_kind(ImportType::ReExport))
+            .into_iter()
+            .filter_map(|id| graph.get_file_path(id))
+            .collect();
+        relink.extend(graph.get_barrel_importers(&barrels));
+
         for path in &deleted_files {
             if let Some(id) = graph.get_file_id(path) {
                 graph.remove_file(id);
@@ -140,14 +206,18 @@ impl GraphBuilder {
             graph.add_file(path.clone(), is_test);
         }
 
-        let files_to_parse: Vec<_> = stale_files
+        let mut files_to_parse: Vec<_> = stale_files
             .into_iter()
             .filter(|f| current_set.contains(f))
-            .chain(new_files.into_iter())
+            .chain(new_files)
             .collect();
 
         let update_count = files_to_parse.len();
 
+        files_to_parse.extend(relink.into_iter().filter(|f| current_set.contains(f)));
+        files_to_parse.sort();
+        files_to_parse.dedup();
+
         for path in &files_to_parse {
             let is_test = self.config.is_test_file(path);
             if graph.contains_file(path) {
@@ -157,60 +227,158 @@ impl GraphBuilder {
 
         let parse_results: Vec<_> = files_to_parse
             .par_iter()
-            .filter_map(|file| {
-                match parser::parse_file(file) {
-                    Ok(imports) => Some((file.clone(), imports)),
-                    Err(e) => {
-                        eprintln!("Warning: Failed to parse {}: {}", file.display(), e);
-                        None
-                    }
-                }
-            })
+            .map(|file| (file.clone(), parse_recovering(file)))
             .collect();
 
-        let mut analyzer = BarrelAnalyzer::new(self.root.clone());
+        for (file, parsed) in parse_results {
+            record_parse_errors(graph, &file, parsed.errors);
+            self.link_imports(graph, &mut analyzer, &file, &parsed.imports);
+        }
+        graph.remove_unused_packages();
+
+        Ok(update_count + deleted_files.len())
+    }
+
+    /// Resolves the imports of `file` and adds the resulting edges to the graph.
+    fn link_imports(
+        &self,
+        graph: &mut DependencyGraph,
+        analyzer: &mut BarrelAnalyzer,
+        file: &Path,
+        imports: &[ImportInfo],
+    ) {
+        let from_id = match graph.get_file_id(file) {
+            Some(id) => id,
+            None => return,
+        };
+
+        for import in imports {
+            if import.import_type == ImportType::Mock && !self.config.follow_mocks {
+                continue;
+            }
+
+            let edge = EdgeInfo::from_import(import);
+
+            for dir in self.resolver.expansion_dirs(file, import) {
+                graph.add_expanded_dir(from_id, &dir);
+            }
 
-        for (file, imports) in parse_results {
-            if let Some(from_id) = graph.get_file_id(&file) {
-                for import in imports {
-                    if let Ok(resolved) = self.resolver.resolve(&file, &import.source) {
-                        let canonical_resolved = std::fs::canonicalize(&resolved).unwrap_or(resolved.clone());
-                        
-                        // Check if this is a barrel import with specific named imports
-                        if self.config.expand_barrels && !import.named_imports.is_empty() && !import.is_namespace {
-                            if analyzer.check_and_cache_barrel(&canonical_resolved) {
-                                let actual_sources = analyzer.resolve_barrel_imports(
-                                    &canonical_resolved,
-                                    &import.named_imports,
-                                );
-                                
-                                for (_name, source_path) in actual_sources {
-                                    if let Some(to_id) = graph.get_file_id(&source_path) {
-                                        graph.add_dependency(from_id, to_id);
-                                    }
-                                }
-                                continue;
-                            }
+            if let Some(module_set) = &import.module_set {
+                for candidate in self.resolver.expand_module_set(file, module_set) {
+                    if let Some(to_id) = graph.get_file_id(&candidate) {
+                        if to_id != from_id {
+                            graph.add_dependency_with_info(from_id, to_id, edge.clone());
                         }
-                        
-                        if let Some(to_id) = graph.get_file_id(&canonical_resolved) {
-                            graph.add_dependency(from_id, to_id);
+                    }
+                }
+                continue;
+            }
+
+            if let Some(partial) = &import.partial {
+                for candidate in self.resolver.expand_partial(file, partial) {
+                    if let Some(to_id) = graph.get_file_id(&candidate) {
+                        if to_id != from_id {
+                            graph.add_dependency_with_info(from_id, to_id, edge.clone());
                         }
                     }
                 }
+                if let Some(node) = graph.get_file_node_mut(from_id) {
+                    node.partial_imports.push(import.source.clone());
+                }
+                continue;
             }
-        }
 
-        Ok(update_count + deleted_files.len())
+            if import.import_type == ImportType::Mock {
+                if let Some(manual_mock) = self.resolver.resolve_manual_mock(&import.source) {
+                    let canonical_mock = std::fs::canonicalize(&manual_mock).unwrap_or(manual_mock);
+                    if let Some(to_id) = graph.get_file_id(&canonical_mock) {
+                        graph.add_dependency_with_info(from_id, to_id, edge.clone());
+                    }
+                }
+            }
+
+            let resolved = match import.import_type {
+                ImportType::AssetImport => self.resolver.resolve_asset_import(file, &import.source),
+                ImportType::TripleSlashReference => self.resolver.resolve_reference(file, &import.source),
+                _ => self.resolver.resolve(file, &import.source),
+            };
+
+            if let Err(ResolveError::NotFound(reason)) = &resolved {
+                if let Some(node) = graph.get_file_node_mut(from_id) {
+                    node.unresolved_imports.push(UnresolvedImport {
+                        specifier: import.source.clone(),
+                        location: import.location.clone(),
+                        reason: reason.clone(),
+                    });
+                }
+            }
+
+            // Symlinked dependencies (`file:`, `npm link`, workspaces) may
+            // point back into the project, so decide on the real path
+            let resolved = resolved.map(|path| std::fs::canonicalize(&path).unwrap_or(path));
+
+            // npm packages link to a virtual node per package, so that a
+            // version bump selects the files importing it
+            let outside_project = match &resolved {
+                Err(ResolveError::External(_)) => true,
+                Ok(path) => path.components().any(|c| c.as_os_str() == "node_modules"),
+                Err(_) => false,
+            };
+            if outside_project {
+                if let Some(package) = resolver::package_name(&import.source) {
+                    let to_id = graph.add_package(&package);
+                    graph.add_dependency_with_info(from_id, to_id, edge);
+                    continue;
+                }
+            }
+
+            if let Ok(canonical_resolved) = resolved {
+                for variant in self.resolver.platform_variants(&canonical_resolved) {
+                    if let Some(to_id) = graph.get_file_id(&variant) {
+                        graph.add_dependency_with_info(from_id, to_id, edge.clone());
+                    }
+                }
+                
+                // Check if this is a barrel import with specific named imports
+                if self.config.expand_barrels
+                    && !import.named_imports.is_empty()
+                    && !import.is_namespace
+                    && analyzer.check_and_cache_barrel(&canonical_resolved)
+                {
+                    graph.add_barrel_import(from_id, &canonical_resolved);
+
+                    // Try to expand barrel imports to actual sources
+                    let actual_sources = analyzer.resolve_barrel_imports(
+                        &canonical_resolved,
+                        &import.named_imports,
+                    );
+                    
+                    // Add edges to actual source files instead of barrel
+                    for (name, source_path) in actual_sources {
+                        if let Some(to_id) = graph.get_file_id(&source_path) {
+                            let expanded = EdgeInfo {
+                                symbols: vec![name],
+                                via_barrel: Some(canonical_resolved.clone()),
+                                ..edge.clone()
+                            };
+                            graph.add_dependency_with_info(from_id, to_id, expanded);
+                        }
+                    }
+                    continue;
+                }
+                
+                // Fallback: add edge to resolved path (non-barrel or namespace import)
+                if let Some(to_id) = graph.get_file_id(&canonical_resolved) {
+                    graph.add_dependency_with_info(from_id, to_id, edge);
+                }
+            }
+        }
     }
 
     fn discover_files(&self) -> Result<Vec<PathBuf>, BuildError> {
         let mut files = Vec::new();
 
-        let walker = WalkBuilder::new(&self.root)
-            .hidden(true)
-            .git_ignore(true)
-            .build();
+        let walker = project_walker(&self.root).build();
 
         for entry in walker {
             let entry = entry.map_err(|e| BuildError::WalkError(e.to_string()))?;
@@ -220,7 +388,7 @@ impl GraphBuilder {
                 continue;
             }
 
-            if !self.config.is_supported_extension(path) {
+            if !self.config.is_supported_extension(path) && !self.config.is_asset_extension(path) {
                 continue;
             }
 
@@ -235,6 +403,95 @@ impl GraphBuilder {
     }
 }
 
+/// Importers of files that a new file can take precedence over or add to:
+/// siblings sharing its name before the first `.` (`Button.tsx` for a new
+/// `Button.ios.tsx`, `x.js` for `x.ts`) and files in the directory of that
+/// name (`x/index.ts` for a new `x.ts`).
+fn shadowed_importers(graph: &DependencyGraph, new_files: &[PathBuf]) -> Vec<PathBuf> {
+    let prefixes: Vec<(PathBuf, String)> = new_files
+        .iter()
+        .filter_map(|file| {
+            let dir = file.parent()?;
+            let stem = file.file_name()?.to_str()?.split('.').next()?;
+            Some((dir.join(stem), format!("{}.", stem)))
+        })
+        .collect();
+
+    let mut importers = Vec::new();
+    for id in graph.get_all_files() {
+        let Some(path) = graph.get_file_path(id) else { continue };
+        let shadowed = prefixes.iter().any(|(stem_path, stem)| {
+            path.starts_with(stem_path)
+                || (path.parent() == stem_path.parent()
+                    && path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with(stem.as_str())))
+        });
+        if shadowed {
+            importers.extend(graph.get_dependents(id).into_iter().filter_map(|d| graph.get_file_path(d)));
+        }
+    }
+    importers
+}
+
+/// Parses `file`, keeping the imports oxc recovered from syntax errors. A file
+/// that can't be read stays in the graph with the read error as its diagnostic.
+fn parse_recovering(file: &Path) -> ParsedFile {
+    parser::parse_file_with_diagnostics(file).unwrap_or_else(|e| ParsedFile {
+        imports: vec![],
+        errors: vec![ParseDiagnostic { location: SourceLocation::default(), message: e.to_string() }],
+    })
+}
+
+fn record_parse_errors(graph: &mut DependencyGraph, file: &Path, errors: Vec<ParseDiagnostic>) {
+    if let Some(id) = graph.get_file_id(file) {
+        if let Some(node) = graph.get_file_node_mut(id) {
+            node.parse_errors = errors;
+        }
+    }
+}
+
+/// Every [`RESOLUTION_FILES`] entry and `tsconfig*.json` under `root`, sorted.
+fn find_resolution_files(root: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = project_walker(root)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            RESOLUTION_FILES.contains(&name.as_ref()) || (name.starts_with("tsconfig") && name.ends_with(".json"))
+        })
+        .map(|entry| entry.into_path())
+        .collect();
+    files.sort();
+    files
+}
+
+fn config_fingerprint(root: &Path, config: &Config, resolution_files: &[PathBuf]) -> u64 {
+    let mut hasher = Xxh3::new();
+    // Only the options the graph is built with; selection options such as
+    // `ignoreTypeEdges` apply to a loaded graph and must not invalidate it
+    let build_options = (
+        &config.extensions,
+        &config.asset_extensions,
+        &config.ignore_patterns,
+        &config.test_patterns,
+        config.expand_barrels,
+        config.follow_mocks,
+        &config.conditions,
+        &config.aliases,
+        &config.platforms,
+    );
+    hasher.update(serde_json::to_string(&build_options).unwrap_or_default().as_bytes());
+    for path in resolution_files {
+        let content = std::fs::read(path).unwrap_or_default();
+        // Relative, so a cache restored in another checkout still matches
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update(&(content.len() as u64).to_le_bytes());
+        hasher.update(&content);
+    }
+    hasher.digest()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -250,11 +507,16 @@ mod tests {
         fs::write(src.join("a.ts"), "export const a = 1;").unwrap();
         fs::write(src.join("b.tsx"), "export const b = 2;").unwrap();
         fs::write(src.join("c.css"), "body {}").unwrap();
+        fs::write(src.join("d.md"), "# notes").unwrap();
 
         let builder = GraphBuilder::new(temp.path().to_path_buf(), Config::default());
         let files = builder.discover_files().unwrap();
 
-        assert_eq!(files.len(), 2);
+        assert_eq!(files.len(), 3);
+
+        let config = Config { asset_extensions: vec![], ..Config::default() };
+        let builder = GraphBuilder::new(temp.path().to_path_buf(), config);
+        assert_eq!(builder.discover_files().unwrap().len(), 2);
     }
 
     #[test]
@@ -296,4 +558,421 @@ mod tests {
         assert_eq!(graph.file_count(), 3);
         assert!(graph.contains_file(&src.join("c.ts")));
     }
+
+    #[test]
+    fn test_build_links_mocked_modules() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+
+        fs::write(src.join("client.ts"), "export const get = () => 1;").unwrap();
+        fs::write(src.join("client.spec.ts"), r#"jest.mock('./client');"#).unwrap();
+
+        let builder = GraphBuilder::new(temp.path().to_path_buf(), Config::default());
+        let graph = builder.build().unwrap();
+        assert_eq!(graph.edge_count(), 1);
+
+        let config = Config { follow_mocks: false, ..Config::default() };
+        let builder = GraphBuilder::new(temp.path().to_path_buf(), config);
+        let graph = builder.build().unwrap();
+        assert_eq!(graph.edge_count(), 0);
+    }
+
+    #[test]
+    fn test_build_marks_type_only_edges() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+
+        fs::write(src.join("types.ts"), "export interface User { id: string }").unwrap();
+        fs::write(src.join("a.ts"), r#"import type { User } from './types';"#).unwrap();
+
+        let builder = GraphBuilder::new(temp.path().to_path_buf(), Config::default());
+        let graph = builder.build().unwrap();
+
+        let a = graph.get_file_id(&src.join("a.ts")).unwrap();
+        let types = graph.get_file_id(&src.join("types.ts")).unwrap();
+        assert!(graph.get_edge_info(a, types).unwrap().type_only);
+    }
+
+    #[test]
+    fn test_build_records_import_kinds_and_symbols() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        let math = src.join("math");
+        fs::create_dir_all(&math).unwrap();
+
+        fs::write(math.join("sum.ts"), "export const sum = (a, b) => a + b;").unwrap();
+        fs::write(math.join("index.ts"), "export { sum } from './sum';").unwrap();
+        fs::write(src.join("Page.tsx"), "export default function Page() {}").unwrap();
+        fs::write(src.join("app.ts"), "import { sum } from './math';\nconst Page = () => import('./Page');").unwrap();
+
+        let builder = GraphBuilder::new(temp.path().to_path_buf(), Config::default());
+        let graph = builder.build().unwrap();
+
+        let app = graph.get_file_id(&src.join("app.ts")).unwrap();
+        let sum = graph.get_file_id(&math.join("sum.ts")).unwrap();
+        let page = graph.get_file_id(&src.join("Page.tsx")).unwrap();
+
+        let edge = graph.get_edge_info(app, sum).unwrap();
+        assert_eq!(edge.kinds, vec![ImportType::StaticImport]);
+        assert_eq!(edge.symbols, vec!["sum"]);
+        assert_eq!(edge.via_barrel, Some(PathBuf::from("src/math/index.ts")));
+
+        let edge = graph.get_edge_info(app, page).unwrap();
+        assert_eq!(edge.kinds, vec![ImportType::DynamicImport]);
+        assert_eq!(edge.symbols, vec!["*"]);
+    }
+
+    #[test]
+    fn test_build_expands_partial_imports() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        let locales = src.join("locales");
+        fs::create_dir_all(&locales).unwrap();
+
+        fs::write(locales.join("en.ts"), "export default {};").unwrap();
+        fs::write(locales.join("de.ts"), "export default {};").unwrap();
+        fs::write(src.join("i18n.ts"), "export const load = (lang) => import(`./locales/${lang}.ts`);").unwrap();
+
+        let builder = GraphBuilder::new(temp.path().to_path_buf(), Config::default());
+        let graph = builder.build().unwrap();
+
+        let i18n = graph.get_file_id(&src.join("i18n.ts")).unwrap();
+        assert_eq!(graph.get_dependencies(i18n).len(), 2);
+
+        let partial = graph.get_partial_imports();
+        assert_eq!(partial.len(), 1);
+        assert_eq!(partial[0].1, "./locales/*.ts");
+    }
+
+    #[test]
+    fn test_build_records_unresolved_imports() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        fs::create_dir_all(src.join("packages").join("ui")).unwrap();
+        fs::write(src.join("packages").join("ui").join("package.json"), r#"{ "name": "@acme/ui" }"#).unwrap();
+        fs::write(temp.path().join("package.json"), r#"{ "dependencies": { "react": "18.0.0" } }"#).unwrap();
+
+        fs::write(
+            src.join("app.ts"),
+            "import React from 'react';\nimport { a } from './missing';\nimport { b } from '@/utils';\nimport { c } from 'utils/c';\n",
+        )
+        .unwrap();
+
+        let builder = GraphBuilder::new(temp.path().to_path_buf(), Config::default());
+        let graph = builder.build().unwrap();
+
+        let unresolved = graph.get_unresolved_imports();
+        let specifiers: Vec<_> = unresolved.iter().map(|(_, u)| (u.specifier.as_str(), u.location.line)).collect();
+        assert_eq!(specifiers, vec![("./missing", 2), ("@/utils", 3), ("utils/c", 4)]);
+
+        let notes = builder.config_notes(&unresolved);
+        assert_eq!(notes.len(), 2);
+        assert!(notes[0].starts_with("No tsconfig.json in the project: 2 unresolved"));
+        assert!(notes[1].starts_with("1 nested package.json files"));
+
+        // A per-package tsconfig counts as well
+        fs::write(src.join("packages").join("ui").join("tsconfig.json"), "{}").unwrap();
+        let builder = GraphBuilder::new(temp.path().to_path_buf(), Config::default());
+        let notes = builder.config_notes(&unresolved);
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].starts_with("1 nested package.json files"));
+    }
+
+    #[test]
+    fn test_update_incremental_picks_up_new_and_deleted_files() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("utils.ts"), "export const x = 1;").unwrap();
+        fs::write(src.join("old.spec.ts"), "import { x } from './utils';").unwrap();
+
+        let builder = GraphBuilder::new(temp.path().to_path_buf(), Config::default());
+        let mut graph = builder.build().unwrap();
+        assert_eq!(builder.update_incremental(&mut graph).unwrap(), 0);
+
+        fs::remove_file(src.join("old.spec.ts")).unwrap();
+        fs::write(src.join("new.spec.ts"), "import { x } from './utils';").unwrap();
+        assert_eq!(builder.update_incremental(&mut graph).unwrap(), 2);
+
+        assert!(graph.get_file_id(&src.join("old.spec.ts")).is_none());
+        let utils = graph.get_file_id(&src.join("utils.ts")).unwrap();
+        let new_test = graph.get_file_id(&src.join("new.spec.ts")).unwrap();
+        assert_eq!(graph.get_dependents(utils), vec![new_test]);
+    }
+
+    #[test]
+    fn test_update_incremental_relinks_barrel_and_glob_importers() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        fs::create_dir_all(src.join("math")).unwrap();
+        fs::create_dir_all(src.join("locales")).unwrap();
+        fs::write(src.join("math").join("add.ts"), "export const add = 1;").unwrap();
+        fs::write(src.join("math").join("sub.ts"), "export const sub = 1;").unwrap();
+        fs::write(src.join("math").join("index.ts"), "export { add } from './add';").unwrap();
+        fs::write(src.join("a.spec.ts"), "import { add, sub } from './math';").unwrap();
+        fs::write(src.join("locales").join("en.ts"), "").unwrap();
+        fs::write(src.join("i18n.spec.ts"), "const all = import.meta.glob('./locales/*.ts');").unwrap();
+
+        let builder = GraphBuilder::new(temp.path().to_path_buf(), Config::default());
+        let mut graph = builder.build().unwrap();
+
+        fs::write(src.join("math").join("index.ts"), "export { add } from './add';\nexport { sub } from './sub';").unwrap();
+        fs::write(src.join("locales").join("fr.ts"), "").unwrap();
+        builder.update_incremental(&mut graph).unwrap();
+
+        let dependencies = |graph: &DependencyGraph, file: &str| {
+            let id = graph.get_file_id(&src.join(file)).unwrap();
+            let mut deps: Vec<_> = graph.get_dependencies(id).into_iter().filter_map(|d| graph.get_file_path(d)).collect();
+            deps.sort();
+            deps
+        };
+        assert_eq!(dependencies(&graph, "a.spec.ts"), vec![src.join("math").join("add.ts"), src.join("math").join("sub.ts")]);
+        assert_eq!(dependencies(&graph, "i18n.spec.ts"), vec![src.join("locales").join("en.ts"), src.join("locales").join("fr.ts")]);
+    }
+
+    #[test]
+    fn test_config_fingerprint_tracks_resolution_files() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        fs::create_dir_all(src.join("ui")).unwrap();
+        fs::write(src.join("app.ts"), "export const app = 1;").unwrap();
+        let fingerprint = || GraphBuilder::new(temp.path().to_path_buf(), Config::default()).config_fingerprint();
+
+        let initial = fingerprint();
+        let graph = GraphBuilder::new(temp.path().to_path_buf(), Config::default()).build().unwrap();
+        assert_eq!(graph.config_fingerprint(), initial);
+
+        fs::write(src.join("app.ts"), "export const app = 2;").unwrap();
+        assert_eq!(fingerprint(), initial);
+
+        let mut seen = vec![initial];
+        for (file, content) in [
+            ("tsconfig.json", r#"{ "compilerOptions": { "paths": { "@/*": ["./src/*"] } } }"#),
+            ("tsconfig.base.json", r#"{ "compilerOptions": { "baseUrl": "." } }"#),
+            ("src/ui/package.json", r#"{ "name": "ui", "exports": "./index.ts" }"#),
+            ("pnpm-workspace.yaml", "packages:\n  - src/*\n"),
+            ("jest.config.json", r#"{ "moduleDirectories": ["src"] }"#),
+        ] {
+            fs::write(temp.path().join(file), content).unwrap();
+            let changed = fingerprint();
+            assert!(!seen.contains(&changed), "{}", file);
+            seen.push(changed);
+        }
+
+        let config = Config { platforms: vec!["ios".to_string()], ..Config::default() };
+        assert_ne!(GraphBuilder::new(temp.path().to_path_buf(), config).config_fingerprint(), fingerprint());
+
+        let config = Config {
+            ignore_type_edges: true,
+            ignore_import_kinds: vec![ImportType::DynamicImport],
+            max_unresolved_imports: Some(0),
+            parse_errors_affect_package: true,
+            default_base: "develop".to_string(),
+            cache_enabled: false,
+            ..Config::default()
+        };
+        assert_eq!(GraphBuilder::new(temp.path().to_path_buf(), config).config_fingerprint(), fingerprint());
+    }
+
+    #[test]
+    fn test_build_links_external_packages() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        let installed = temp.path().join("node_modules").join("@scope").join("ui");
+        fs::create_dir_all(&installed).unwrap();
+        fs::write(installed.join("package.json"), r#"{ "name": "@scope/ui", "main": "index.js" }"#).unwrap();
+        fs::write(installed.join("index.js"), "").unwrap();
+        fs::write(temp.path().join("package.json"), r#"{ "dependencies": { "react": "18.0.0" } }"#).unwrap();
+
+        fs::write(
+            src.join("app.ts"),
+            "import React from 'react';\nimport { Button } from '@scope/ui/button';\nimport fs from 'node:fs';\nimport path from 'path';\nimport { readFile } from 'fs/promises';\n",
+        )
+        .unwrap();
+        fs::write(src.join("app.test.ts"), "import { app } from './app';\nimport { render } from '@scope/ui';\n").unwrap();
+
+        let graph = GraphBuilder::new(temp.path().to_path_buf(), Config::default()).build().unwrap();
+
+        assert_eq!(graph.file_count(), 2);
+        assert_eq!(graph.package_count(), 2);
+        let app = graph.get_file_id(&src.join("app.ts")).unwrap();
+        let react = graph.get_package_id("react").unwrap();
+        let ui = graph.get_package_id("@scope/ui").unwrap();
+        assert!(graph.get_dependencies(app).contains(&react));
+        assert!(graph.get_dependencies(app).contains(&ui));
+
+        let test = graph.get_file_id(&src.join("app.test.ts")).unwrap();
+        assert!(graph.get_transitive_dependents(&[react]).contains(&test));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_build_links_symlinked_dependency_to_project_file() {
+        let temp = TempDir::new().unwrap();
+        let shared = temp.path().join("packages").join("shared");
+        fs::create_dir_all(&shared).unwrap();
+        fs::write(shared.join("package.json"), r#"{ "name": "@acme/shared", "main": "index.ts" }"#).unwrap();
+        fs::write(shared.join("index.ts"), "export const x = 1;").unwrap();
+        // A nested install reached through a tsconfig path alias
+        let web = temp.path().join("apps").join("web");
+        fs::create_dir_all(web.join("node_modules").join("@acme")).unwrap();
+        std::os::unix::fs::symlink(&shared, web.join("node_modules").join("@acme").join("shared")).unwrap();
+        fs::write(
+            web.join("tsconfig.json"),
+            r#"{ "compilerOptions": { "paths": { "@acme/shared": ["./node_modules/@acme/shared/index.ts"] } } }"#,
+        )
+        .unwrap();
+        fs::write(web.join("app.spec.ts"), "import { x } from '@acme/shared';").unwrap();
+
+        let graph = GraphBuilder::new(temp.path().to_path_buf(), Config::default()).build().unwrap();
+
+        assert_eq!(graph.package_count(), 0);
+        let test = graph.get_file_id(&web.join("app.spec.ts")).unwrap();
+        let index = graph.get_file_id(&shared.join("index.ts")).unwrap();
+        assert_eq!(graph.get_dependencies(test), vec![index]);
+    }
+
+    #[test]
+    fn test_build_fans_out_to_platform_variants() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+
+        fs::write(src.join("Button.tsx"), "export const Button = 1;").unwrap();
+        fs::write(src.join("Button.ios.tsx"), "export const Button = 2;").unwrap();
+        fs::write(src.join("Button.android.tsx"), "export const Button = 3;").unwrap();
+        fs::write(src.join("Button.test.tsx"), "import { Button } from './Button';").unwrap();
+
+        let config = Config { platforms: vec!["ios".to_string(), "android".to_string()], ..Config::default() };
+        let graph = GraphBuilder::new(temp.path().to_path_buf(), config).build().unwrap();
+
+        let test = graph.get_file_id(&src.join("Button.test.tsx")).unwrap();
+        assert_eq!(graph.get_dependencies(test).len(), 3);
+
+        let ios = graph.get_file_id(&src.join("Button.ios.tsx")).unwrap();
+        assert!(graph.get_transitive_dependents(&[ios]).contains(&test));
+    }
+
+    #[test]
+    fn test_build_expands_module_sets() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        let routes = src.join("routes");
+        fs::create_dir_all(&routes).unwrap();
+
+        fs::write(routes.join("home.tsx"), "export default {};").unwrap();
+        fs::write(routes.join("about.tsx"), "export default {};").unwrap();
+        fs::write(src.join("router.ts"), "export const routes = import.meta.glob('./routes/*.tsx');").unwrap();
+        fs::write(src.join("legacy.js"), "const ctx = require.context('./routes', false, /\\.tsx$/);").unwrap();
+
+        let builder = GraphBuilder::new(temp.path().to_path_buf(), Config::default());
+        let graph = builder.build().unwrap();
+
+        let router = graph.get_file_id(&src.join("router.ts")).unwrap();
+        assert_eq!(graph.get_dependencies(router).len(), 2);
+        let legacy = graph.get_file_id(&src.join("legacy.js")).unwrap();
+        assert_eq!(graph.get_dependencies(legacy).len(), 2);
+    }
+
+    #[test]
+    fn test_build_tracks_asset_dependencies() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        let styles = src.join("styles");
+        fs::create_dir_all(&styles).unwrap();
+
+        fs::write(styles.join("_variables.scss"), "$primary: blue;").unwrap();
+        fs::write(src.join("Button.module.scss"), "@use 'styles/variables';").unwrap();
+        fs::write(src.join("fixture.json"), "{}").unwrap();
+        fs::write(temp.path().join("package.json"), "{}").unwrap();
+        fs::write(temp.path().join("tsconfig.json"), "{}").unwrap();
+        fs::write(src.join("Button.tsx"), "import styles from './Button.module.scss';").unwrap();
+        fs::write(src.join("Button.spec.tsx"), r#"
+            import { Button } from './Button';
+            import data from './fixture.json';
+        "#).unwrap();
+
+        let builder = GraphBuilder::new(temp.path().to_path_buf(), Config::default());
+        let graph = builder.build().unwrap();
+
+        let partial = graph.get_file_id(&styles.join("_variables.scss")).unwrap();
+        let test = graph.get_file_id(&src.join("Button.spec.tsx")).unwrap();
+        assert!(graph.get_transitive_dependents(&[partial]).contains(&test));
+
+        let fixture = graph.get_file_id(&src.join("fixture.json")).unwrap();
+        assert_eq!(graph.get_dependents(fixture), vec![test]);
+        assert!(graph.get_file_id(&temp.path().join("package.json")).is_none());
+        assert!(graph.get_file_id(&temp.path().join("tsconfig.json")).is_none());
+    }
+
+    #[test]
+    fn test_build_single_file_components() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+
+        fs::write(src.join("format.ts"), "export const format = (s: string) => s;").unwrap();
+        fs::write(src.join("Card.vue"), r#"
+<script setup lang="ts">
+import { format } from './format';
+</script>
+"#).unwrap();
+        fs::write(src.join("Card.spec.ts"), "import Card from './Card';").unwrap();
+
+        let mut config = Config::default();
+        config.extensions.push(".vue".to_string());
+        let builder = GraphBuilder::new(temp.path().to_path_buf(), config);
+        let graph = builder.build().unwrap();
+
+        let format = graph.get_file_id(&src.join("format.ts")).unwrap();
+        let test = graph.get_file_id(&src.join("Card.spec.ts")).unwrap();
+        assert!(graph.get_transitive_dependents(&[format]).contains(&test));
+    }
+
+    #[test]
+    fn test_build_keeps_files_with_syntax_errors() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+
+        fs::write(src.join("utils.ts"), "export const a = 1;").unwrap();
+        fs::write(src.join("broken.spec.ts"), "import { a } from './utils';\nconst x = ;\n").unwrap();
+
+        let builder = GraphBuilder::new(temp.path().to_path_buf(), Config::default());
+        let graph = builder.build().unwrap();
+
+        let utils = graph.get_file_id(&src.join("utils.ts")).unwrap();
+        let test = graph.get_file_id(&src.join("broken.spec.ts")).unwrap();
+        assert_eq!(graph.get_dependents(utils), vec![test]);
+
+        let diagnostics = graph.get_parse_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].0, src.join("broken.spec.ts"));
+        assert_eq!(diagnostics[0].1[0].location.line, 2);
+    }
+
+    #[test]
+    fn test_build_links_legacy_typescript_forms() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+
+        fs::write(src.join("globals.d.ts"), "declare const VERSION: string;").unwrap();
+        fs::write(src.join("fs.ts"), "const fs = {}; export = fs;").unwrap();
+        fs::write(src.join("legacy.spec.ts"), "/// <reference path=\"globals.d.ts\" />\nimport fs = require('./fs');\n").unwrap();
+
+        let builder = GraphBuilder::new(temp.path().to_path_buf(), Config::default());
+        let graph = builder.build().unwrap();
+
+        let test = graph.get_file_id(&src.join("legacy.spec.ts")).unwrap();
+        let mut deps: Vec<_> = graph.get_dependencies(test)
+            .into_iter()
+            .filter_map(|id| graph.get_file_path(id))
+            .collect();
+        deps.sort();
+        assert_eq!(deps, vec![src.join("fs.ts"), src.join("globals.d.ts")]);
+    }
 }
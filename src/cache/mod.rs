@@ -2,9 +2,9 @@ use std::path::{Path, PathBuf};
 use std::fs;
 use thiserror::Error;
 
-use crate::graph::{DependencyGraph, SerializedGraph};
+use crate::graph::{DependencyGraph, SerializedGraph, GRAPH_FORMAT_VERSION};
 
-const CACHE_VERSION: u32 = 1;
+const CACHE_VERSION: u32 = GRAPH_FORMAT_VERSION;
 const CACHE_DIR: &str = ".scopetest";
 const CACHE_FILE: &str = "cache.bin";
 
@@ -21,12 +21,13 @@ pub enum CacheError {
 }
 
 pub struct CacheManager {
+    project_root: PathBuf,
     cache_dir: PathBuf,
 }
 
 impl CacheManager {
     pub fn new(project_root: &Path) -> Self {
-        Self { cache_dir: project_root.join(CACHE_DIR) }
+        Self { project_root: project_root.to_path_buf(), cache_dir: project_root.join(CACHE_DIR) }
     }
 
     fn cache_path(&self) -> PathBuf {
@@ -50,7 +51,10 @@ impl CacheManager {
             return Err(CacheError::VersionMismatch);
         }
 
-        Ok(Some(DependencyGraph::deserialize(serialized)))
+        // Paths in the cache are relative, so it may come from another checkout
+        let mut graph = DependencyGraph::deserialize(serialized);
+        graph.set_root(&self.project_root);
+        Ok(Some(graph))
     }
 
     pub fn save(&self, graph: &DependencyGraph) -> Result<(), CacheError> {
@@ -104,6 +108,28 @@ mod tests {
         assert_eq!(loaded.unwrap().file_count(), 2);
     }
 
+    #[test]
+    fn test_cache_restored_in_another_checkout() {
+        let built = TempDir::new().unwrap();
+        let moved = TempDir::new().unwrap();
+        for root in [built.path(), moved.path()] {
+            fs::create_dir_all(root.join("src")).unwrap();
+            fs::write(root.join("src/a.ts"), "export const a = 1;").unwrap();
+        }
+
+        let mut graph = DependencyGraph::with_root(built.path());
+        graph.add_file(built.path().join("src/a.ts"), false);
+        CacheManager::new(built.path()).save(&graph).unwrap();
+        fs::create_dir_all(moved.path().join(CACHE_DIR)).unwrap();
+        fs::copy(built.path().join(CACHE_DIR).join(CACHE_FILE), moved.path().join(CACHE_DIR).join(CACHE_FILE)).unwrap();
+
+        let mut loaded = CacheManager::new(moved.path()).load().unwrap().unwrap();
+        let id = loaded.get_file_id(&moved.path().join("src/a.ts")).unwrap();
+        assert_eq!(loaded.get_relative_path(id), Some(Path::new("src/a.ts")));
+        assert_eq!(loaded.get_file_path(id), Some(fs::canonicalize(moved.path()).unwrap().join("src/a.ts")));
+        assert!(loaded.get_stale_files().is_empty());
+    }
+
     #[test]
     fn test_cache_invalidate() {
         let temp = TempDir::new().unwrap();
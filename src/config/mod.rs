@@ -2,8 +2,21 @@ use std::path::Path;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::resolver::DEFAULT_CONDITIONS;
+use crate::parser::ImportType;
+
 const CONFIG_FILE: &str = ".scopetestrc.json";
 
+/// Manifests and lockfiles that share an asset extension. They are read as
+/// configuration (and fingerprinted with it), not tracked as graph nodes.
+const MANIFEST_FILES: &[&str] = &[
+    CONFIG_FILE,
+    "package.json",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "jest.config.json",
+];
+
 #[derive(Error, Debug)]
 pub enum ConfigError {
     #[error("Failed to read config: {0}")]
@@ -23,6 +36,10 @@ pub struct Config {
     
     #[serde(default = "default_extensions")]
     pub extensions: Vec<String>,
+
+    /// Non-JS files tracked as graph nodes (stylesheets, fixtures, icons, ...)
+    #[serde(default = "default_asset_extensions")]
+    pub asset_extensions: Vec<String>,
     
     #[serde(default = "default_cache_enabled")]
     pub cache_enabled: bool,
@@ -32,6 +49,40 @@ pub struct Config {
     
     #[serde(default = "default_expand_barrels")]
     pub expand_barrels: bool,
+
+    /// Treat `jest.mock` / `vi.mock` style module paths as dependencies
+    #[serde(default = "default_follow_mocks")]
+    pub follow_mocks: bool,
+
+    /// Don't let type-only imports propagate changes to dependents
+    #[serde(default)]
+    pub ignore_type_edges: bool,
+
+    /// Import kinds that don't propagate changes to dependents (e.g. `dynamicImport`)
+    #[serde(default)]
+    pub ignore_import_kinds: Vec<ImportType>,
+
+    /// package.json `exports`/`imports` conditions, in order of preference (`default` is always tried last)
+    #[serde(default = "default_conditions")]
+    pub conditions: Vec<String>,
+
+    /// Extra import aliases for settings that live in JS configs (e.g. vitest
+    /// `resolve.alias`); keys starting with `^` are jest-style regexes
+    #[serde(default)]
+    pub aliases: serde_json::Map<String, serde_json::Value>,
+
+    /// Platform suffixes (`ios` for `Button.ios.tsx`); an import of `./Button`
+    /// depends on every platform variant next to it
+    #[serde(default)]
+    pub platforms: Vec<String>,
+
+    /// `scopetest doctor` fails when more imports than this don't resolve
+    #[serde(default)]
+    pub max_unresolved_imports: Option<usize>,
+
+    /// Treat files that failed to parse cleanly as depending on everything in their package
+    #[serde(default)]
+    pub parse_errors_affect_package: bool,
 }
 
 fn default_test_patterns() -> Vec<String> {
@@ -65,12 +116,33 @@ fn default_extensions() -> Vec<String> {
         ".jsx".to_string(),
         ".mjs".to_string(),
         ".cjs".to_string(),
+        ".mts".to_string(),
+        ".cts".to_string(),
+        ".d.ts".to_string(),
+    ]
+}
+
+fn default_conditions() -> Vec<String> {
+    DEFAULT_CONDITIONS.iter().map(|c| c.to_string()).collect()
+}
+
+fn default_asset_extensions() -> Vec<String> {
+    vec![
+        ".css".to_string(),
+        ".scss".to_string(),
+        ".sass".to_string(),
+        ".less".to_string(),
+        ".json".to_string(),
+        ".svg".to_string(),
+        ".graphql".to_string(),
+        ".gql".to_string(),
     ]
 }
 
 fn default_cache_enabled() -> bool { true }
 fn default_base() -> String { "main".to_string() }
 fn default_expand_barrels() -> bool { true }
+fn default_follow_mocks() -> bool { true }
 
 impl Default for Config {
     fn default() -> Self {
@@ -78,9 +150,18 @@ impl Default for Config {
             test_patterns: default_test_patterns(),
             ignore_patterns: default_ignore_patterns(),
             extensions: default_extensions(),
+            asset_extensions: default_asset_extensions(),
             cache_enabled: default_cache_enabled(),
             default_base: default_base(),
             expand_barrels: default_expand_barrels(),
+            follow_mocks: default_follow_mocks(),
+            ignore_type_edges: false,
+            ignore_import_kinds: Vec::new(),
+            conditions: default_conditions(),
+            aliases: serde_json::Map::new(),
+            platforms: Vec::new(),
+            max_unresolved_imports: None,
+            parse_errors_affect_package: false,
         }
     }
 }
@@ -134,12 +215,29 @@ impl Config {
         path_str.contains("node_modules")
     }
 
+    /// Matches by file name suffix so multi-part extensions like `.d.ts` work.
     pub fn is_supported_extension(&self, path: &Path) -> bool {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .map(|name| self.extensions.iter().any(|ext| name.len() > ext.len() && name.ends_with(ext.as_str())))
+            .unwrap_or(false)
+    }
+
+    /// Manifests, lockfiles and `tsconfig*.json` / `jsconfig*.json` are
+    /// excluded so they are not read and hashed on every staleness check.
+    pub fn is_asset_extension(&self, path: &Path) -> bool {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+        if MANIFEST_FILES.contains(&name)
+            || ((name.starts_with("tsconfig") || name.starts_with("jsconfig")) && name.ends_with(".json"))
+        {
+            return false;
+        }
+
         path.extension()
             .and_then(|ext| ext.to_str())
             .map(|ext| {
                 let ext_with_dot = format!(".{}", ext);
-                self.extensions.contains(&ext_with_dot)
+                self.asset_extensions.contains(&ext_with_dot)
             })
             .unwrap_or(false)
     }
@@ -183,5 +281,23 @@ mod tests {
         assert!(config.is_supported_extension(Path::new("foo.tsx")));
         assert!(config.is_supported_extension(Path::new("foo.js")));
         assert!(!config.is_supported_extension(Path::new("foo.css")));
+        assert!(config.is_supported_extension(Path::new("foo.mts")));
+        assert!(config.is_supported_extension(Path::new("foo.cts")));
+        assert!(config.is_supported_extension(Path::new("globals.d.ts")));
+        assert!(!config.is_supported_extension(Path::new("foo.gts")));
+    }
+
+    #[test]
+    fn test_is_asset_extension() {
+        let config = Config::default();
+
+        assert!(config.is_asset_extension(Path::new("Button.module.scss")));
+        assert!(config.is_asset_extension(Path::new("fixture.json")));
+        assert!(config.is_asset_extension(Path::new("icon.svg")));
+        assert!(!config.is_asset_extension(Path::new("foo.ts")));
+        assert!(!config.is_asset_extension(Path::new("packages/ui/package.json")));
+        assert!(!config.is_asset_extension(Path::new("package-lock.json")));
+        assert!(!config.is_asset_extension(Path::new("tsconfig.base.json")));
+        assert!(!config.is_asset_extension(Path::new(".scopetestrc.json")));
     }
 }
@@ -0,0 +1,341 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use serde_json::{Map, Value};
+
+/// Manifests and lockfiles in which an external package's version can change.
+const DEPENDENCY_FILES: &[&str] = &[
+    "package.json",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+];
+
+const MANIFEST_FIELDS: &[&str] = &["dependencies", "devDependencies", "peerDependencies", "optionalDependencies"];
+
+/// Every package listed in a dependency file, with all of its versions, and
+/// the packages that depend on each of them.
+#[derive(Default)]
+struct Packages {
+    versions: BTreeMap<String, BTreeSet<String>>,
+    dependents: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Packages {
+    fn add_version(&mut self, name: &str, version: &str) {
+        self.versions.entry(name.to_string()).or_default().insert(version.to_string());
+    }
+
+    fn add_dependent(&mut self, name: &str, dependent: &str) {
+        if name != dependent {
+            self.dependents.entry(name.to_string()).or_default().insert(dependent.to_string());
+        }
+    }
+}
+
+pub fn is_dependency_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|name| DEPENDENCY_FILES.contains(&name))
+}
+
+/// Packages whose versions (or, in package.json, version ranges) differ between
+/// two revisions of the dependency file `file_name`. Packages that were added
+/// or removed count as changed, and so does every package that depends on a
+/// changed one: a bump of `debug` pulled in by `a` changes `a` as well, which
+/// is what the project imports.
+pub fn changed_packages(file_name: &str, old: &str, new: &str) -> Vec<String> {
+    let old = parse_packages(file_name, old);
+    let new = parse_packages(file_name, new);
+    let names: BTreeSet<&String> = old.versions.keys().chain(new.versions.keys()).collect();
+    let mut changed: BTreeSet<String> = names
+        .into_iter()
+        .filter(|name| old.versions.get(*name) != new.versions.get(*name))
+        .cloned()
+        .collect();
+
+    let mut queue: Vec<String> = changed.iter().cloned().collect();
+    while let Some(name) = queue.pop() {
+        for dependent in [&old, &new].into_iter().filter_map(|lock| lock.dependents.get(&name)).flatten() {
+            if changed.insert(dependent.clone()) {
+                queue.push(dependent.clone());
+            }
+        }
+    }
+    changed.into_iter().collect()
+}
+
+fn parse_packages(file_name: &str, content: &str) -> Packages {
+    match file_name {
+        "package.json" => manifest_ranges(content),
+        "package-lock.json" | "npm-shrinkwrap.json" => npm_lock_packages(content),
+        "yarn.lock" => yarn_lock_packages(content),
+        "pnpm-lock.yaml" => pnpm_lock_packages(content),
+        _ => Packages::default(),
+    }
+}
+
+fn manifest_ranges(content: &str) -> Packages {
+    let mut packages = Packages::default();
+    let Ok(manifest) = serde_json::from_str::<Value>(content) else {
+        return packages;
+    };
+    for field in MANIFEST_FIELDS {
+        let Some(dependencies) = manifest.get(field).and_then(|d| d.as_object()) else { continue };
+        for (name, range) in dependencies {
+            let range = range.as_str().map(String::from).unwrap_or_else(|| range.to_string());
+            packages.add_version(name, &range);
+        }
+    }
+    packages
+}
+
+/// lockfileVersion 2 and 3 list every install under `packages` keyed by its
+/// `node_modules/...` path; version 1 nests `dependencies` instead.
+fn npm_lock_packages(content: &str) -> Packages {
+    let mut packages = Packages::default();
+    let Ok(lock) = serde_json::from_str::<Value>(content) else {
+        return packages;
+    };
+
+    if let Some(installs) = lock.get("packages").and_then(|p| p.as_object()) {
+        for (key, entry) in installs {
+            let Some((parent, name)) = key.rsplit_once("node_modules/") else { continue };
+            if let Some(version) = entry.get("version").and_then(|v| v.as_str()) {
+                packages.add_version(name, version);
+            }
+            // `node_modules/a/node_modules/debug` is installed for `a`
+            if let Some((_, parent)) = parent.trim_end_matches('/').rsplit_once("node_modules/") {
+                packages.add_dependent(name, parent);
+            }
+            for field in MANIFEST_FIELDS {
+                for dependency in entry.get(field).and_then(|d| d.as_object()).into_iter().flat_map(|d| d.keys()) {
+                    packages.add_dependent(dependency, name);
+                }
+            }
+        }
+    } else if let Some(dependencies) = lock.get("dependencies").and_then(|d| d.as_object()) {
+        collect_npm_v1(dependencies, None, &mut packages);
+    }
+    packages
+}
+
+fn collect_npm_v1(dependencies: &Map<String, Value>, parent: Option<&str>, packages: &mut Packages) {
+    for (name, entry) in dependencies {
+        if let Some(version) = entry.get("version").and_then(|v| v.as_str()) {
+            packages.add_version(name, version);
+        }
+        if let Some(parent) = parent {
+            packages.add_dependent(name, parent);
+        }
+        for required in entry.get("requires").and_then(|r| r.as_object()).into_iter().flat_map(|r| r.keys()) {
+            packages.add_dependent(required, name);
+        }
+        if let Some(nested) = entry.get("dependencies").and_then(|d| d.as_object()) {
+            collect_npm_v1(nested, Some(name), packages);
+        }
+    }
+}
+
+/// Classic (`lodash@^4.17.0, lodash@^4.17.21:` / `version "4.17.21"`) and
+/// berry (`"lodash@npm:^4.17.21":` / `version: 4.17.21`) lockfiles. Entries
+/// list what they require under `dependencies:` as `debug "^2.6.9"` (classic)
+/// or `debug: "npm:^2.6.9"` (berry).
+fn yarn_lock_packages(content: &str) -> Packages {
+    let mut packages = Packages::default();
+    let mut names: Vec<String> = Vec::new();
+    let mut in_dependencies = false;
+
+    for line in content.lines() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if !line.starts_with(' ') {
+            names = line
+                .trim_end()
+                .trim_end_matches(':')
+                .split(',')
+                .filter_map(|descriptor| descriptor_name(descriptor.trim().trim_matches('"')))
+                .map(String::from)
+                .collect();
+            names.dedup();
+            in_dependencies = false;
+        } else if !line.starts_with("    ") {
+            let field = line.trim();
+            in_dependencies = matches!(field, "dependencies:" | "optionalDependencies:");
+            if let Some(version) = field.strip_prefix("version") {
+                let version = version.trim_start_matches(':').trim().trim_matches('"');
+                for name in &names {
+                    packages.add_version(name, version);
+                }
+            }
+        } else if in_dependencies {
+            if let Some(dependency) = dependency_name(line.trim(), &[' ', ':']) {
+                for name in &names {
+                    packages.add_dependent(dependency, name);
+                }
+            }
+        }
+    }
+    packages
+}
+
+/// `@scope/pkg` from `@scope/pkg@^1.0.0` or `@scope/pkg@npm:^1.0.0`.
+fn descriptor_name(descriptor: &str) -> Option<&str> {
+    let at = descriptor.get(1..)?.find('@')? + 1;
+    Some(&descriptor[..at])
+}
+
+/// The package name a dependency line starts with, quoted or ending at one of
+/// `ends`.
+fn dependency_name<'a>(line: &'a str, ends: &[char]) -> Option<&'a str> {
+    let name = match line.strip_prefix(['"', '\'']) {
+        Some(quoted) => quoted.split(['"', '\'']).next()?,
+        None => line.split(ends).next()?,
+    };
+    (!name.is_empty()).then_some(name)
+}
+
+/// Keys of the `packages` (and, from v9, `snapshots`) sections: `/lodash/4.17.21`
+/// in v5, `/lodash@4.17.21` in v6 and `lodash@4.17.21(peer@1.0.0)` in v9. Their
+/// `dependencies:` list `react: 18.2.0` one level further in.
+fn pnpm_lock_packages(content: &str) -> Packages {
+    let mut packages = Packages::default();
+    let mut in_packages = false;
+    let mut current: Option<String> = None;
+    let mut in_dependencies = false;
+
+    for line in content.lines() {
+        if !line.starts_with(' ') && !line.trim().is_empty() {
+            in_packages = matches!(line.trim_end(), "packages:" | "snapshots:");
+            current = None;
+            continue;
+        }
+        if !in_packages {
+            continue;
+        }
+        // Package keys are indented by exactly two spaces, their fields by
+        // four and dependency entries by six
+        if let Some(field) = line.strip_prefix("    ") {
+            if !field.starts_with(' ') {
+                in_dependencies = matches!(field.trim_end(), "dependencies:" | "optionalDependencies:");
+            } else if in_dependencies {
+                if let (Some(name), Some(dependency)) = (&current, dependency_name(field.trim(), &[':'])) {
+                    packages.add_dependent(dependency, name);
+                }
+            }
+            continue;
+        }
+        let Some(key) = line.strip_prefix("  ") else { continue };
+        if key.starts_with(' ') || !key.trim_end().ends_with(':') {
+            continue;
+        }
+        in_dependencies = false;
+        let key = key.trim_end().trim_end_matches(':').trim_matches(|c| c == '\'' || c == '"');
+        let key = key.strip_prefix('/').unwrap_or(key);
+        let key = key.split('(').next().unwrap_or(key);
+
+        let entry = match key.rsplit_once('/') {
+            Some((name, version)) if version.starts_with(|c: char| c.is_ascii_digit()) => {
+                // v5 appends peers with `_`
+                Some((name, version.split('_').next().unwrap_or(version)))
+            }
+            _ => descriptor_name(key).map(|name| (name, &key[name.len() + 1..])),
+        };
+        current = entry.map(|(name, _)| name.to_string());
+        if let Some((name, version)) = entry {
+            packages.add_version(name, version);
+        }
+    }
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_json_ranges() {
+        let old = r#"{ "version": "1.0.0", "dependencies": { "react": "^18.2.0", "lodash": "^4.17.0" } }"#;
+        let new = r#"{ "version": "1.1.0", "dependencies": { "react": "^18.3.0", "lodash": "^4.17.0" },
+                       "devDependencies": { "vitest": "^1.0.0" } }"#;
+        assert_eq!(changed_packages("package.json", old, new), vec!["react", "vitest"]);
+        assert!(changed_packages("package.json", old, old).is_empty());
+    }
+
+    #[test]
+    fn test_npm_lockfiles() {
+        let old = r#"{ "lockfileVersion": 3, "packages": {
+            "": { "name": "app" },
+            "node_modules/react": { "version": "18.2.0" },
+            "node_modules/@scope/pkg": { "version": "1.0.0" },
+            "node_modules/a/node_modules/debug": { "version": "2.6.9" }
+        } }"#;
+        let new = old.replace("\"2.6.9\"", "\"2.6.8\"");
+        assert_eq!(changed_packages("package-lock.json", old, &new), vec!["a", "debug"]);
+
+        let v1_old = r#"{ "dependencies": { "a": { "version": "1.0.0", "dependencies": { "b": { "version": "2.0.0" } } } } }"#;
+        let v1_new = v1_old.replace("2.0.0", "2.1.0");
+        assert_eq!(changed_packages("package-lock.json", v1_old, &v1_new), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_transitive_bumps_change_their_dependents() {
+        // `ms` is hoisted, so only the `dependencies` of the packages tell who pulls it in
+        let npm = r#"{ "lockfileVersion": 3, "packages": {
+            "": { "name": "app", "dependencies": { "express": "^4.0.0", "react": "^18.0.0" } },
+            "node_modules/express": { "version": "4.18.0", "dependencies": { "debug": "2.6.9" } },
+            "node_modules/debug": { "version": "2.6.9", "dependencies": { "ms": "2.0.0" } },
+            "node_modules/ms": { "version": "2.0.0" },
+            "node_modules/react": { "version": "18.2.0" }
+        } }"#;
+        let bumped = npm.replace(r#""version": "2.0.0""#, r#""version": "2.1.3""#);
+        assert_eq!(changed_packages("package-lock.json", npm, &bumped), vec!["debug", "express", "ms"]);
+
+        let npm_v1 = r#"{ "dependencies": {
+            "express": { "version": "4.18.0", "requires": { "debug": "2.6.9" } },
+            "debug": { "version": "2.6.9" }
+        } }"#;
+        let bumped = npm_v1.replace("\"2.6.9\" }", "\"2.6.8\" }");
+        assert_eq!(changed_packages("package-lock.json", npm_v1, &bumped), vec!["debug", "express"]);
+
+        let classic = "express@^4.0.0:\n  version \"4.18.0\"\n  dependencies:\n    debug \"2.6.9\"\n\ndebug@2.6.9:\n  version \"2.6.9\"\n";
+        let bumped = classic.replace("version \"2.6.9\"", "version \"2.6.8\"");
+        assert_eq!(changed_packages("yarn.lock", classic, &bumped), vec!["debug", "express"]);
+
+        let berry = "\"express@npm:^4.0.0\":\n  version: 4.18.0\n  dependencies:\n    debug: \"npm:2.6.9\"\n\n\"debug@npm:2.6.9\":\n  version: 2.6.9\n";
+        let bumped = berry.replace("version: 2.6.9", "version: 2.6.8");
+        assert_eq!(changed_packages("yarn.lock", berry, &bumped), vec!["debug", "express"]);
+
+        let pnpm = "lockfileVersion: '9.0'\n\npackages:\n\n  debug@2.6.9:\n    resolution: {integrity: x}\n\n  express@4.18.0:\n    resolution: {integrity: y}\n\nsnapshots:\n\n  debug@2.6.9: {}\n\n  express@4.18.0:\n    dependencies:\n      debug: 2.6.9\n";
+        let bumped = pnpm.replace("debug@2.6.9", "debug@2.6.8").replace("debug: 2.6.9", "debug: 2.6.8");
+        assert_eq!(changed_packages("pnpm-lock.yaml", pnpm, &bumped), vec!["debug", "express"]);
+    }
+
+    #[test]
+    fn test_yarn_lockfiles() {
+        let classic = "# yarn lockfile v1\n\n\"@babel/core@^7.0.0\", \"@babel/core@^7.1.0\":\n  version \"7.1.0\"\n  resolved \"...\"\n\nlodash@^4.17.21:\n  version \"4.17.21\"\n";
+        let versions = yarn_lock_packages(classic).versions;
+        assert_eq!(versions["@babel/core"], BTreeSet::from(["7.1.0".to_string()]));
+        assert_eq!(versions["lodash"], BTreeSet::from(["4.17.21".to_string()]));
+
+        let berry = "__metadata:\n  version: 6\n\n\"lodash@npm:^4.17.21\":\n  version: 4.17.21\n  resolution: \"lodash@npm:4.17.21\"\n";
+        let bumped = berry.replace("version: 4.17.21", "version: 4.17.22");
+        assert_eq!(changed_packages("yarn.lock", berry, &bumped), vec!["lodash"]);
+    }
+
+    #[test]
+    fn test_pnpm_lockfiles() {
+        let v5 = "lockfileVersion: 5.4\n\npackages:\n\n  /react-dom/18.2.0_react@18.2.0:\n    resolution: {integrity: x}\n  /@scope/pkg/1.0.0:\n    dev: false\n";
+        let versions = pnpm_lock_packages(v5).versions;
+        assert_eq!(versions["react-dom"], BTreeSet::from(["18.2.0".to_string()]));
+        assert_eq!(versions["@scope/pkg"], BTreeSet::from(["1.0.0".to_string()]));
+
+        let v9 = "lockfileVersion: '9.0'\n\nimporters:\n\n  .:\n    dependencies:\n      react:\n        specifier: ^18\n\npackages:\n\n  '@scope/pkg@1.0.0':\n    resolution: {integrity: x}\n\nsnapshots:\n\n  react-dom@18.2.0(react@18.2.0):\n    dependencies:\n      react: 18.2.0\n";
+        let versions = pnpm_lock_packages(v9).versions;
+        assert_eq!(versions.keys().collect::<Vec<_>>(), vec!["@scope/pkg", "react-dom"]);
+
+        let bumped = v9.replace("react-dom@18.2.0", "react-dom@18.3.1");
+        assert_eq!(changed_packages("pnpm-lock.yaml", v9, &bumped), vec!["react-dom"]);
+    }
+}
@@ -1,4 +1,7 @@
-use std::path::PathBuf;
+mod lockfile;
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use thiserror::Error;
 
@@ -18,6 +21,8 @@ pub struct ChangeSet {
     pub added: Vec<PathBuf>,
     pub deleted: Vec<PathBuf>,
     pub renamed: Vec<(PathBuf, PathBuf)>,
+    /// External packages whose versions changed in a manifest or lockfile
+    pub packages: Vec<String>,
 }
 
 impl ChangeSet {
@@ -37,6 +42,7 @@ impl ChangeSet {
             && self.added.is_empty()
             && self.deleted.is_empty()
             && self.renamed.is_empty()
+            && self.packages.is_empty()
     }
 }
 
@@ -96,7 +102,9 @@ impl GitChangeDetector {
         }
 
         let stdout = String::from_utf8_lossy(&output.stdout);
-        self.parse_diff_output(&stdout)
+        let mut changeset = self.parse_diff_output(&stdout)?;
+        changeset.packages = self.changed_packages(&changeset, base_ref, None);
+        Ok(changeset)
     }
 
     pub fn detect_changes_since(&self, since_ref: &str) -> Result<ChangeSet, GitError> {
@@ -124,7 +132,42 @@ impl GitChangeDetector {
         }
 
         let stdout = String::from_utf8_lossy(&output.stdout);
-        self.parse_diff_output(&stdout)
+        let mut changeset = self.parse_diff_output(&stdout)?;
+        changeset.packages = self.changed_packages(&changeset, since_ref, Some("HEAD"));
+        Ok(changeset)
+    }
+
+    /// Packages whose versions differ between `base_ref` and `head` (the
+    /// working tree when `None`) in the changed manifests and lockfiles.
+    fn changed_packages(&self, changeset: &ChangeSet, base_ref: &str, head: Option<&str>) -> Vec<String> {
+        let mut packages = BTreeSet::new();
+        let changed = changeset.all_changed().into_iter().chain(changeset.deleted.iter().cloned());
+
+        for path in changed.filter(|p| lockfile::is_dependency_file(p)) {
+            let (Ok(relative), Some(file_name)) = (path.strip_prefix(&self.repo_root), path.file_name()) else {
+                continue;
+            };
+            let old = self.show(base_ref, relative).unwrap_or_default();
+            let new = match head {
+                Some(rev) => self.show(rev, relative),
+                None => std::fs::read_to_string(&path).ok(),
+            }
+            .unwrap_or_default();
+            packages.extend(lockfile::changed_packages(&file_name.to_string_lossy(), &old, &new));
+        }
+
+        packages.into_iter().collect()
+    }
+
+    /// Contents of `path`, relative to the repository root, at revision `rev`.
+    fn show(&self, rev: &str, path: &Path) -> Option<String> {
+        let output = Command::new("git")
+            .arg("show")
+            .arg(format!("{}:./{}", rev, path.to_string_lossy()))
+            .current_dir(&self.repo_root)
+            .output()
+            .ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
     }
 
     fn parse_diff_output(&self, output: &str) -> Result<ChangeSet, GitError> {
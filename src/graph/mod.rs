@@ -1,91 +1,368 @@
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
-use petgraph::graph::{DiGraph, NodeIndex};
+use std::fs::Metadata;
+use std::time::{Duration, SystemTime};
+use petgraph::stable_graph::{NodeIndex, StableDiGraph};
+use petgraph::visit::{EdgeRef, IntoEdgeReferences};
 use petgraph::Direction;
 use serde::{Deserialize, Serialize};
 
+use crate::parser::{ImportInfo, ImportType, ParseDiagnostic, SourceLocation};
+
+/// Stays valid until its own file is removed, however many other files are
+/// added or removed in between.
 pub type FileId = NodeIndex<u32>;
 
+/// Bumped whenever the serialized layout changes so stale caches are rebuilt.
+pub const GRAPH_FORMAT_VERSION: u32 = 12;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileNode {
+    /// Relative to the graph's root (see [`DependencyGraph::with_root`])
     pub path: PathBuf,
     pub is_test: bool,
+    /// Modification time in nanoseconds since the epoch, or 0 when it was too
+    /// recent to rule out a later edit within the same timestamp tick
     pub last_modified: u64,
+    pub size: u64,
+    /// xxh3 of the file's bytes, which is stable across machines and Rust releases
     pub content_hash: u64,
+    /// Specifiers that were only partially known (e.g. `./locales/*.ts`) and were
+    /// expanded to every matching file, so selection through them is approximate
+    #[serde(default)]
+    pub partial_imports: Vec<String>,
+    /// Syntax errors oxc recovered from; the node's edges may be incomplete
+    #[serde(default)]
+    pub parse_errors: Vec<ParseDiagnostic>,
+    /// Imports of project files that matched nothing, so their edges are missing
+    #[serde(default)]
+    pub unresolved_imports: Vec<UnresolvedImport>,
+    /// Barrels whose re-exports named imports were expanded through. The
+    /// edges change with the barrel's exports, not just with this file.
+    #[serde(default)]
+    pub barrel_imports: Vec<PathBuf>,
+    /// Directories that partial specifiers and module sets were expanded in,
+    /// whose added or removed files change this file's edges
+    #[serde(default)]
+    pub expanded_dirs: Vec<PathBuf>,
+    /// Set on the virtual node standing for an external npm package, which has
+    /// no file behind its `path` (`node_modules/<name>`)
+    #[serde(default)]
+    pub package: Option<String>,
+}
+
+/// An import that should point into the project but didn't resolve.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnresolvedImport {
+    pub specifier: String,
+    pub location: SourceLocation,
+    pub reason: String,
 }
 
 impl FileNode {
     pub fn new(path: PathBuf, is_test: bool) -> Self {
-        let last_modified = std::fs::metadata(&path)
-            .and_then(|m| m.modified())
-            .map(|t| t.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs())
-            .unwrap_or(0);
-        
-        let content_hash = std::fs::read(&path)
-            .map(|content| {
-                use std::hash::{Hash, Hasher};
-                let mut hasher = std::collections::hash_map::DefaultHasher::new();
-                content.hash(&mut hasher);
-                hasher.finish()
-            })
-            .unwrap_or(0);
-        
-        Self { path, is_test, last_modified, content_hash }
+        // Metadata first: if the file changes while it's read, the stored
+        // mtime is older than the content and the next check rehashes it
+        let (last_modified, size) = std::fs::metadata(&path).map(|m| file_stamp(&m)).unwrap_or((0, 0));
+        let content_hash = std::fs::read(&path).map(|content| hash_content(&content)).unwrap_or(0);
+
+        Self {
+            path,
+            is_test,
+            last_modified,
+            size,
+            content_hash,
+            partial_imports: Vec::new(),
+            parse_errors: Vec::new(),
+            unresolved_imports: Vec::new(),
+            barrel_imports: Vec::new(),
+            expanded_dirs: Vec::new(),
+            package: None,
+        }
+    }
+
+    /// Virtual node for the external package `name`.
+    pub fn package(name: &str) -> Self {
+        Self {
+            path: package_path(name),
+            is_test: false,
+            last_modified: 0,
+            size: 0,
+            content_hash: 0,
+            partial_imports: Vec::new(),
+            parse_errors: Vec::new(),
+            unresolved_imports: Vec::new(),
+            barrel_imports: Vec::new(),
+            expanded_dirs: Vec::new(),
+            package: Some(name.to_string()),
+        }
+    }
+
+    pub fn is_package(&self) -> bool {
+        self.package.is_some()
+    }
+}
+
+/// Files modified this recently are always rehashed: filesystem timestamps
+/// are coarse, so a same-size edit right after could keep the same mtime.
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+/// Modification time (in nanoseconds, 0 if within [`RACY_WINDOW`] of now) and size.
+fn file_stamp(metadata: &Metadata) -> (u64, u64) {
+    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    let settled = SystemTime::now().duration_since(modified).is_ok_and(|age| age >= RACY_WINDOW);
+    let nanos = if settled {
+        modified.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_nanos() as u64
+    } else {
+        0
+    };
+    (nanos, metadata.len())
+}
+
+fn hash_content(content: &[u8]) -> u64 {
+    xxhash_rust::xxh3::xxh3_64(content)
+}
+
+/// Path of the virtual node for an external package. Nothing under
+/// `node_modules` is a project file, so it never collides with one.
+fn package_path(name: &str) -> PathBuf {
+    Path::new("node_modules").join(name)
+}
+
+/// Metadata about why one file depends on another.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdgeInfo {
+    /// Every import behind this edge is erased at runtime (`import type` and friends)
+    pub type_only: bool,
+    /// The import statement in the importing file that created this edge
+    pub location: Option<SourceLocation>,
+    /// How the target is imported, sorted; several kinds when it's imported more than once
+    pub kinds: Vec<ImportType>,
+    /// Names used from the target, sorted: named imports, `default`, and `*`
+    /// for a namespace import. Empty for side-effect imports and `require`.
+    pub symbols: Vec<String>,
+    /// Barrel file the import names, when it was expanded to the file that
+    /// actually defines the symbols
+    pub via_barrel: Option<PathBuf>,
+}
+
+impl EdgeInfo {
+    /// The edge for a single import.
+    pub fn from_import(import: &ImportInfo) -> Self {
+        let mut symbols = import.named_imports.clone();
+        if import.has_default {
+            symbols.push("default".to_string());
+        }
+        if import.is_namespace {
+            symbols.push("*".to_string());
+        }
+        symbols.sort();
+        symbols.dedup();
+
+        Self {
+            type_only: import.is_type_only,
+            location: Some(import.location.clone()),
+            kinds: vec![import.import_type],
+            symbols,
+            via_barrel: None,
+        }
+    }
+
+    /// Whether any import behind this edge is of the given kind.
+    pub fn has_kind(&self, kind: ImportType) -> bool {
+        self.kinds.contains(&kind)
+    }
+
+    /// Combines two imports of the same target: the edge stays type-only only
+    /// if both imports are, and points at the first runtime import when there is one.
+    /// Kinds and symbols are united (an edge without kinds, e.g. from
+    /// [`DependencyGraph::add_dependency`], stays unknown); the edge only stays
+    /// barrel-expanded if both are.
+    pub fn merge(&mut self, other: &EdgeInfo) {
+        let prefer_other = self.location.is_none() || (self.type_only && !other.type_only);
+        if prefer_other && other.location.is_some() {
+            self.location = other.location.clone();
+        }
+        self.type_only &= other.type_only;
+
+        if self.kinds.is_empty() || other.kinds.is_empty() {
+            self.kinds.clear();
+        } else {
+            self.kinds.extend(other.kinds.iter().copied());
+            self.kinds.sort();
+            self.kinds.dedup();
+        }
+        self.symbols.extend(other.symbols.iter().cloned());
+        self.symbols.sort();
+        self.symbols.dedup();
+        if other.via_barrel.is_none() {
+            self.via_barrel = None;
+        }
     }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SerializedGraph {
     pub version: u32,
+    pub config_fingerprint: u64,
     pub nodes: Vec<FileNode>,
-    pub edges: Vec<(u32, u32)>,
+    pub edges: Vec<(u32, u32, EdgeInfo)>,
 }
 
 #[derive(Debug)]
 pub struct DependencyGraph {
-    graph: DiGraph<FileNode, ()>,
+    /// Canonical project root. Nodes are stored relative to it, so a cached
+    /// graph stays valid when the project is checked out somewhere else.
+    root: PathBuf,
+    /// Stable, so removing a node doesn't move another one into its index
+    graph: StableDiGraph<FileNode, EdgeInfo>,
     path_to_id: HashMap<PathBuf, FileId>,
+    /// Hash of the configuration the graph was built with (see
+    /// [`GraphBuilder::config_fingerprint`](crate::builder::GraphBuilder::config_fingerprint))
+    config_fingerprint: u64,
+    /// Set when file stamps were refreshed in memory (see
+    /// [`get_stale_files`](Self::get_stale_files)) and a saved copy is outdated
+    dirty: bool,
 }
 
 impl DependencyGraph {
+    /// A graph without a root, which stores paths as they are given.
     pub fn new() -> Self {
         Self {
-            graph: DiGraph::new(),
+            root: PathBuf::new(),
+            graph: StableDiGraph::new(),
             path_to_id: HashMap::new(),
+            config_fingerprint: 0,
+            dirty: false,
+        }
+    }
+
+    /// A graph storing files relative to the project at `root`. Paths passed
+    /// in and returned are still absolute.
+    pub fn with_root(root: &Path) -> Self {
+        let mut graph = Self::new();
+        graph.set_root(root);
+        graph
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Moves the graph to the project at `root`, e.g. after restoring a cache
+    /// that was written on another machine.
+    pub fn set_root(&mut self, root: &Path) {
+        self.root = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    }
+
+    pub fn config_fingerprint(&self) -> u64 {
+        self.config_fingerprint
+    }
+
+    pub fn set_config_fingerprint(&mut self, fingerprint: u64) {
+        self.config_fingerprint = fingerprint;
+    }
+
+    /// Whether something was updated in memory that a saved copy of the graph
+    /// doesn't have, even though no file changed.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Where a stored (root-relative) path is on disk.
+    pub fn absolute_path(&self, path: &Path) -> PathBuf {
+        self.root.join(path)
+    }
+
+    /// The stored form of `path`: canonicalized, then made relative to the
+    /// root. Files outside the root keep their absolute path.
+    fn relative_path(&self, path: &Path) -> PathBuf {
+        if path.is_relative() {
+            return path.to_path_buf();
+        }
+        let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        match canonical.strip_prefix(&self.root) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => canonical,
         }
     }
 
     pub fn add_file(&mut self, path: PathBuf, is_test: bool) -> FileId {
-        let canonical_path = std::fs::canonicalize(&path).unwrap_or(path);
-        
-        if let Some(&id) = self.path_to_id.get(&canonical_path) {
+        let relative = self.relative_path(&path);
+
+        if let Some(&id) = self.path_to_id.get(&relative) {
             return id;
         }
-        
-        let node = FileNode::new(canonical_path.clone(), is_test);
+
+        let mut node = FileNode::new(self.absolute_path(&relative), is_test);
+        node.path = relative.clone();
         let id = self.graph.add_node(node);
-        self.path_to_id.insert(canonical_path, id);
+        self.path_to_id.insert(relative, id);
+        id
+    }
+
+    /// Returns the virtual node for an external package, adding it if needed.
+    pub fn add_package(&mut self, name: &str) -> FileId {
+        let path = package_path(name);
+        if let Some(&id) = self.path_to_id.get(&path) {
+            return id;
+        }
+
+        let id = self.graph.add_node(FileNode::package(name));
+        self.path_to_id.insert(path, id);
         id
     }
 
+    pub fn get_package_id(&self, name: &str) -> Option<FileId> {
+        self.path_to_id.get(&package_path(name)).copied()
+    }
+
     pub fn add_dependency(&mut self, from: FileId, to: FileId) {
-        if !self.graph.contains_edge(from, to) {
-            self.graph.add_edge(from, to, ());
+        self.add_dependency_with_info(from, to, EdgeInfo::default());
+    }
+
+    pub fn add_dependency_with_info(&mut self, from: FileId, to: FileId, mut info: EdgeInfo) {
+        info.via_barrel = info.via_barrel.map(|barrel| self.relative_path(&barrel));
+        match self.graph.find_edge(from, to) {
+            Some(edge) => {
+                if let Some(existing) = self.graph.edge_weight_mut(edge) {
+                    existing.merge(&info);
+                }
+            }
+            None => {
+                self.graph.add_edge(from, to, info);
+            }
         }
     }
 
+    pub fn get_edge_info(&self, from: FileId, to: FileId) -> Option<&EdgeInfo> {
+        self.graph.find_edge(from, to).and_then(|e| self.graph.edge_weight(e))
+    }
+
+    /// Looks up an absolute or root-relative path.
     pub fn get_file_id(&self, path: &Path) -> Option<FileId> {
-        if let Some(&id) = self.path_to_id.get(path) {
+        let stored = path.strip_prefix(&self.root).unwrap_or(path);
+        if let Some(&id) = self.path_to_id.get(stored) {
             return Some(id);
         }
-        if let Ok(canonical) = std::fs::canonicalize(path) {
-            return self.path_to_id.get(&canonical).copied();
+        if path.is_absolute() {
+            return self.path_to_id.get(&self.relative_path(path)).copied();
         }
         None
     }
 
-    pub fn get_file_path(&self, id: FileId) -> Option<&Path> {
+    /// Absolute path of a file. Virtual package nodes aren't on disk and keep
+    /// their `node_modules/<name>` path.
+    pub fn get_file_path(&self, id: FileId) -> Option<PathBuf> {
+        let node = self.graph.node_weight(id)?;
+        if node.is_package() {
+            return Some(node.path.clone());
+        }
+        Some(self.absolute_path(&node.path))
+    }
+
+    /// Path of a file relative to the root, as stored in the cache.
+    pub fn get_relative_path(&self, id: FileId) -> Option<&Path> {
         self.graph.node_weight(id).map(|n| n.path.as_path())
     }
 
@@ -93,6 +370,84 @@ impl DependencyGraph {
         self.graph.node_weight(id)
     }
 
+    pub fn get_file_node_mut(&mut self, id: FileId) -> Option<&mut FileNode> {
+        self.graph.node_weight_mut(id)
+    }
+
+    /// Files that were parsed with errors, sorted by path.
+    pub fn get_parse_diagnostics(&self) -> Vec<(PathBuf, Vec<ParseDiagnostic>)> {
+        let mut result: Vec<_> = self.graph
+            .node_weights()
+            .filter(|n| !n.parse_errors.is_empty())
+            .map(|n| (self.absolute_path(&n.path), n.parse_errors.clone()))
+            .collect();
+        result.sort_by(|a, b| a.0.cmp(&b.0));
+        result
+    }
+
+    /// All `(file, specifier)` pairs whose dependencies were approximated from a
+    /// partially known specifier.
+    pub fn get_partial_imports(&self) -> Vec<(PathBuf, String)> {
+        let mut result: Vec<_> = self.graph
+            .node_weights()
+            .flat_map(|n| n.partial_imports.iter().map(|s| (self.absolute_path(&n.path), s.clone())))
+            .collect();
+        result.sort();
+        result
+    }
+
+    /// Records that `file` expanded named imports through `barrel`.
+    pub fn add_barrel_import(&mut self, file: FileId, barrel: &Path) {
+        let barrel = self.relative_path(barrel);
+        if let Some(node) = self.graph.node_weight_mut(file) {
+            if !node.barrel_imports.contains(&barrel) {
+                node.barrel_imports.push(barrel);
+            }
+        }
+    }
+
+    /// Records that `file` matched a partial specifier or module set against
+    /// the files in `dir`.
+    pub fn add_expanded_dir(&mut self, file: FileId, dir: &Path) {
+        let dir = self.relative_path(dir);
+        if let Some(node) = self.graph.node_weight_mut(file) {
+            if !node.expanded_dirs.contains(&dir) {
+                node.expanded_dirs.push(dir);
+            }
+        }
+    }
+
+    /// Files that expanded named imports through one of `barrels`.
+    pub fn get_barrel_importers(&self, barrels: &[PathBuf]) -> Vec<PathBuf> {
+        let barrels: HashSet<PathBuf> = barrels.iter().map(|b| self.relative_path(b)).collect();
+        self.graph
+            .node_weights()
+            .filter(|n| n.barrel_imports.iter().any(|b| barrels.contains(b)))
+            .map(|n| self.absolute_path(&n.path))
+            .collect()
+    }
+
+    /// Files with a partial specifier or module set expanded in a directory
+    /// containing one of `paths`.
+    pub fn get_expanding_importers(&self, paths: &[PathBuf]) -> Vec<PathBuf> {
+        let paths: Vec<PathBuf> = paths.iter().map(|p| self.relative_path(p)).collect();
+        self.graph
+            .node_weights()
+            .filter(|n| n.expanded_dirs.iter().any(|dir| paths.iter().any(|p| p.starts_with(dir))))
+            .map(|n| self.absolute_path(&n.path))
+            .collect()
+    }
+
+    /// Imports that failed to resolve, sorted by file and line.
+    pub fn get_unresolved_imports(&self) -> Vec<(PathBuf, UnresolvedImport)> {
+        let mut result: Vec<_> = self.graph
+            .node_weights()
+            .flat_map(|n| n.unresolved_imports.iter().map(|u| (self.absolute_path(&n.path), u.clone())))
+            .collect();
+        result.sort_by(|a, b| (&a.0, a.1.location.line).cmp(&(&b.0, b.1.location.line)));
+        result
+    }
+
     pub fn get_dependents(&self, file: FileId) -> Vec<FileId> {
         self.graph.neighbors_directed(file, Direction::Incoming).collect()
     }
@@ -101,7 +456,40 @@ impl DependencyGraph {
         self.graph.neighbors_directed(file, Direction::Outgoing).collect()
     }
 
+    /// Dependents reached through edges accepted by `follow`.
+    pub fn get_dependents_filtered<F>(&self, file: FileId, follow: F) -> Vec<FileId>
+    where
+        F: Fn(&EdgeInfo) -> bool,
+    {
+        self.graph
+            .edges_directed(file, Direction::Incoming)
+            .filter(|e| follow(e.weight()))
+            .map(|e| e.source())
+            .collect()
+    }
+
+    /// Dependencies reached through edges accepted by `follow`.
+    pub fn get_dependencies_filtered<F>(&self, file: FileId, follow: F) -> Vec<FileId>
+    where
+        F: Fn(&EdgeInfo) -> bool,
+    {
+        self.graph
+            .edges_directed(file, Direction::Outgoing)
+            .filter(|e| follow(e.weight()))
+            .map(|e| e.target())
+            .collect()
+    }
+
     pub fn get_transitive_dependents(&self, files: &[FileId]) -> HashSet<FileId> {
+        self.get_transitive_dependents_filtered(files, |_| true)
+    }
+
+    /// Like [`get_transitive_dependents`](Self::get_transitive_dependents), but only
+    /// walks edges accepted by `follow`.
+    pub fn get_transitive_dependents_filtered<F>(&self, files: &[FileId], follow: F) -> HashSet<FileId>
+    where
+        F: Fn(&EdgeInfo) -> bool,
+    {
         let mut result = HashSet::new();
         
         for &start in files {
@@ -115,7 +503,7 @@ impl DependencyGraph {
                 visited.insert(current);
                 result.insert(current);
                 
-                for dependent in self.get_dependents(current) {
+                for dependent in self.get_dependents_filtered(current, &follow) {
                     if !visited.contains(&dependent) {
                         queue.push(dependent);
                     }
@@ -137,8 +525,13 @@ impl DependencyGraph {
         self.graph.node_indices().collect()
     }
 
+    /// Number of project files, not counting external packages.
     pub fn file_count(&self) -> usize {
-        self.graph.node_count()
+        self.graph.node_weights().filter(|n| !n.is_package()).count()
+    }
+
+    pub fn package_count(&self) -> usize {
+        self.graph.node_weights().filter(|n| n.is_package()).count()
     }
 
     pub fn edge_count(&self) -> usize {
@@ -146,34 +539,43 @@ impl DependencyGraph {
     }
 
     pub fn remove_file(&mut self, id: FileId) {
-        if let Some(node) = self.graph.node_weight(id) {
-            self.path_to_id.remove(&node.path.clone());
+        if let Some(node) = self.graph.remove_node(id) {
+            self.path_to_id.remove(&node.path);
+        }
+    }
+
+    /// Removes external packages that no file imports anymore.
+    pub fn remove_unused_packages(&mut self) {
+        let unused: Vec<_> = self.graph
+            .node_indices()
+            .filter(|&id| self.graph[id].is_package() && self.get_dependents(id).is_empty())
+            .collect();
+        for id in unused {
+            self.remove_file(id);
         }
-        self.graph.remove_node(id);
     }
 
     pub fn contains_file(&self, path: &Path) -> bool {
         self.get_file_id(path).is_some()
     }
 
+    /// Nodes are written densely, so removed files leave no holes in the cache.
     pub fn serialize(&self) -> SerializedGraph {
-        let nodes: Vec<FileNode> = self.graph
-            .node_indices()
-            .filter_map(|id| self.graph.node_weight(id).cloned())
-            .collect();
-        
-        let edges: Vec<(u32, u32)> = self.graph
-            .edge_indices()
-            .filter_map(|e| {
-                self.graph.edge_endpoints(e).map(|(a, b)| (a.index() as u32, b.index() as u32))
-            })
+        let ids: Vec<FileId> = self.graph.node_indices().collect();
+        let position: HashMap<FileId, u32> = ids.iter().enumerate().map(|(i, &id)| (id, i as u32)).collect();
+
+        let nodes: Vec<FileNode> = ids.iter().map(|&id| self.graph[id].clone()).collect();
+        let edges: Vec<(u32, u32, EdgeInfo)> = (&self.graph)
+            .edge_references()
+            .map(|e| (position[&e.source()], position[&e.target()], e.weight().clone()))
             .collect();
 
-        SerializedGraph { version: 1, nodes, edges }
+        SerializedGraph { version: GRAPH_FORMAT_VERSION, config_fingerprint: self.config_fingerprint, nodes, edges }
     }
 
+    /// The restored graph has no root until [`DependencyGraph::set_root`] is called.
     pub fn deserialize(data: SerializedGraph) -> Self {
-        let mut graph = DiGraph::new();
+        let mut graph = StableDiGraph::new();
         let mut path_to_id = HashMap::new();
 
         for node in data.nodes {
@@ -182,40 +584,53 @@ impl DependencyGraph {
             path_to_id.insert(path, id);
         }
 
-        for (from, to) in data.edges {
+        for (from, to, info) in data.edges {
             let from_id = NodeIndex::new(from as usize);
             let to_id = NodeIndex::new(to as usize);
-            graph.add_edge(from_id, to_id, ());
+            graph.add_edge(from_id, to_id, info);
         }
 
-        Self { graph, path_to_id }
+        Self { root: PathBuf::new(), graph, path_to_id, config_fingerprint: data.config_fingerprint, dirty: false }
     }
 
+    /// Paths of all project files, not counting external packages.
     pub fn get_all_paths(&self) -> Vec<PathBuf> {
-        self.path_to_id.keys().cloned().collect()
+        self.graph.node_weights().filter(|n| !n.is_package()).map(|n| self.absolute_path(&n.path)).collect()
     }
 
-    pub fn get_stale_files(&self) -> Vec<PathBuf> {
+    /// Files whose content changed, or that disappeared, since they were
+    /// hashed. Matching mtime and size skip the rehash; a file whose metadata
+    /// changed but whose content didn't (e.g. after `git checkout`) isn't
+    /// stale, and its new metadata is recorded and marks the graph dirty.
+    pub fn get_stale_files(&mut self) -> Vec<PathBuf> {
         let mut stale = Vec::new();
-        
-        for (path, &id) in &self.path_to_id {
-            if let Some(node) = self.graph.node_weight(id) {
-                if !path.exists() {
-                    stale.push(path.clone());
-                    continue;
-                }
-                
-                let current_mtime = std::fs::metadata(path)
-                    .and_then(|m| m.modified())
-                    .map(|t| t.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs())
-                    .unwrap_or(0);
-                
-                if current_mtime != node.last_modified {
-                    stale.push(path.clone());
+
+        for node in self.graph.node_weights_mut() {
+            if node.is_package() {
+                continue;
+            }
+            let path = self.root.join(&node.path);
+            let Ok(metadata) = std::fs::metadata(&path) else {
+                stale.push(path);
+                continue;
+            };
+
+            let (last_modified, size) = file_stamp(&metadata);
+            if node.last_modified != 0 && last_modified == node.last_modified && size == node.size {
+                continue;
+            }
+            match std::fs::read(&path) {
+                Ok(content) if hash_content(&content) == node.content_hash => {
+                    if (last_modified, size) != (node.last_modified, node.size) {
+                        node.last_modified = last_modified;
+                        node.size = size;
+                        self.dirty = true;
+                    }
                 }
+                _ => stale.push(path),
             }
         }
-        
+
         stale
     }
 
@@ -229,8 +644,9 @@ impl DependencyGraph {
     }
 
     pub fn update_file(&mut self, path: &Path, is_test: bool) -> Option<FileId> {
-        if let Some(&id) = self.path_to_id.get(path) {
-            let new_node = FileNode::new(path.to_path_buf(), is_test);
+        if let Some(id) = self.get_file_id(path) {
+            let mut new_node = FileNode::new(self.get_file_path(id)?, is_test);
+            new_node.path = self.graph[id].path.clone();
             if let Some(node) = self.graph.node_weight_mut(id) {
                 *node = new_node;
             }
@@ -250,7 +666,9 @@ impl Default for DependencyGraph {
 
 impl Clone for DependencyGraph {
     fn clone(&self) -> Self {
-        Self::deserialize(self.serialize())
+        let mut graph = Self::deserialize(self.serialize());
+        graph.root = self.root.clone();
+        graph
     }
 }
 
@@ -264,7 +682,7 @@ mod tests {
         let id = graph.add_file(PathBuf::from("/test/a.ts"), false);
         
         assert_eq!(graph.file_count(), 1);
-        assert_eq!(graph.get_file_path(id), Some(Path::new("/test/a.ts")));
+        assert_eq!(graph.get_file_path(id), Some(PathBuf::from("/test/a.ts")));
     }
 
     #[test]
@@ -309,6 +727,103 @@ mod tests {
         assert_eq!(tests.len(), 2);
     }
 
+    #[test]
+    fn test_stale_files_decided_by_content() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("a.ts");
+        let set_mtime = |secs| {
+            let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+            std::fs::File::options().write(true).open(&path).unwrap().set_modified(mtime).unwrap();
+        };
+        std::fs::write(&path, "export const a = 1;").unwrap();
+        set_mtime(1_700_000_000);
+
+        let mut graph = DependencyGraph::new();
+        let id = graph.add_file(path.clone(), false);
+        assert!(graph.get_stale_files().is_empty());
+
+        // Touched, e.g. by a checkout, without a content change
+        assert!(!graph.is_dirty());
+        set_mtime(1_700_000_060);
+        assert!(graph.get_stale_files().is_empty());
+        assert_eq!(graph.get_file_node(id).unwrap().last_modified, 1_700_000_060_000_000_000);
+        assert!(graph.is_dirty());
+
+        // Edited back to an older mtime
+        std::fs::write(&path, "export const a = 22;").unwrap();
+        set_mtime(1_600_000_000);
+        assert_eq!(graph.get_stale_files().len(), 1);
+    }
+
+    #[test]
+    fn test_recently_modified_files_are_rehashed() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("a.ts");
+        std::fs::write(&path, "export const a = 1;").unwrap();
+
+        let mut graph = DependencyGraph::new();
+        let id = graph.add_file(path.clone(), false);
+        assert_eq!(graph.get_file_node(id).unwrap().last_modified, 0);
+
+        // Same size, possibly within the same timestamp tick
+        std::fs::write(&path, "export const a = 2;").unwrap();
+        assert_eq!(graph.get_stale_files().len(), 1);
+    }
+
+    #[test]
+    fn test_settled_stamp_recorded_for_recent_file() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("a.ts");
+        std::fs::write(&path, "export const a = 1;").unwrap();
+
+        let mut graph = DependencyGraph::new();
+        let id = graph.add_file(path.clone(), false);
+        assert!(graph.get_stale_files().is_empty());
+        assert!(!graph.is_dirty());
+
+        // Once outside the racy window, the real stamp is kept from then on
+        let mtime = SystemTime::now() - Duration::from_secs(60);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(mtime).unwrap();
+        assert!(graph.get_stale_files().is_empty());
+        assert!(graph.is_dirty());
+        assert_ne!(graph.get_file_node(id).unwrap().last_modified, 0);
+    }
+
+    #[test]
+    fn test_rooted_paths_of_files_and_packages() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = std::fs::canonicalize(temp.path()).unwrap();
+        std::fs::write(root.join("a.ts"), "import _ from 'lodash';").unwrap();
+
+        let mut graph = DependencyGraph::with_root(&root);
+        let file = graph.add_file(root.join("a.ts"), false);
+        let lodash = graph.add_package("lodash");
+
+        assert_eq!(graph.get_relative_path(file), Some(Path::new("a.ts")));
+        assert_eq!(graph.get_file_path(file), Some(root.join("a.ts")));
+        assert_eq!(graph.get_file_path(lodash), Some(PathBuf::from("node_modules/lodash")));
+    }
+
+    #[test]
+    fn test_remove_file_keeps_other_ids() {
+        let mut graph = DependencyGraph::new();
+        let a = graph.add_file(PathBuf::from("/a.ts"), false);
+        let b = graph.add_file(PathBuf::from("/b.ts"), false);
+        let c = graph.add_file(PathBuf::from("/c.spec.ts"), true);
+        graph.add_dependency(c, b);
+
+        graph.remove_file(a);
+
+        assert_eq!(graph.get_file_id(Path::new("/c.spec.ts")), Some(c));
+        assert_eq!(graph.get_file_path(c), Some(PathBuf::from("/c.spec.ts")));
+        assert_eq!(graph.get_dependencies(c), vec![b]);
+
+        let restored = DependencyGraph::deserialize(graph.serialize());
+        let c = restored.get_file_id(Path::new("/c.spec.ts")).unwrap();
+        let b = restored.get_file_id(Path::new("/b.ts")).unwrap();
+        assert_eq!(restored.get_dependencies(c), vec![b]);
+    }
+
     #[test]
     fn test_serialize_deserialize() {
         let mut graph = DependencyGraph::new();
@@ -322,4 +837,94 @@ mod tests {
         assert_eq!(restored.file_count(), 2);
         assert_eq!(restored.edge_count(), 1);
     }
+
+    #[test]
+    fn test_type_only_edges_merge() {
+        let mut graph = DependencyGraph::new();
+        let a = graph.add_file(PathBuf::from("/test/a.ts"), false);
+        let b = graph.add_file(PathBuf::from("/test/b.ts"), false);
+
+        graph.add_dependency_with_info(a, b, EdgeInfo { type_only: true, ..Default::default() });
+        assert!(graph.get_edge_info(a, b).unwrap().type_only);
+
+        graph.add_dependency_with_info(a, b, EdgeInfo { type_only: false, ..Default::default() });
+        assert_eq!(graph.edge_count(), 1);
+        assert!(!graph.get_edge_info(a, b).unwrap().type_only);
+    }
+
+    #[test]
+    fn test_transitive_dependents_filtered() {
+        let mut graph = DependencyGraph::new();
+        let types = graph.add_file(PathBuf::from("/test/types.ts"), false);
+        let a = graph.add_file(PathBuf::from("/test/a.ts"), false);
+        let test = graph.add_file(PathBuf::from("/test/a.spec.ts"), true);
+
+        graph.add_dependency_with_info(a, types, EdgeInfo { type_only: true, ..Default::default() });
+        graph.add_dependency(test, a);
+
+        assert_eq!(graph.get_transitive_dependents(&[types]).len(), 3);
+        let runtime = graph.get_transitive_dependents_filtered(&[types], |e| !e.type_only);
+        assert_eq!(runtime.len(), 1);
+        assert!(runtime.contains(&types));
+    }
+
+    #[test]
+    fn test_edge_location_prefers_runtime_import() {
+        let mut graph = DependencyGraph::new();
+        let a = graph.add_file(PathBuf::from("/test/a.ts"), false);
+        let b = graph.add_file(PathBuf::from("/test/b.ts"), false);
+        let at_line = |line| Some(SourceLocation { line, column: 1, text: String::new() });
+
+        graph.add_dependency_with_info(a, b, EdgeInfo { type_only: true, location: at_line(1), ..Default::default() });
+        graph.add_dependency_with_info(a, b, EdgeInfo { type_only: false, location: at_line(5), ..Default::default() });
+        graph.add_dependency_with_info(a, b, EdgeInfo { type_only: false, location: at_line(9), ..Default::default() });
+
+        assert_eq!(graph.get_edge_info(a, b).unwrap().location.as_ref().unwrap().line, 5);
+    }
+
+    #[test]
+    fn test_serialize_preserves_edge_info() {
+        let mut graph = DependencyGraph::new();
+        let a = graph.add_file(PathBuf::from("/test/a.ts"), false);
+        let b = graph.add_file(PathBuf::from("/test/b.ts"), false);
+        let edge = EdgeInfo {
+            type_only: true,
+            kinds: vec![ImportType::StaticImport],
+            symbols: vec!["Props".to_string()],
+            ..Default::default()
+        };
+        graph.add_dependency_with_info(a, b, edge.clone());
+
+        let restored = DependencyGraph::deserialize(graph.serialize());
+        assert_eq!(restored.get_edge_info(a, b), Some(&edge));
+    }
+
+    #[test]
+    fn test_edge_kinds_and_symbols_merge() {
+        let mut graph = DependencyGraph::new();
+        let a = graph.add_file(PathBuf::from("/test/a.ts"), false);
+        let b = graph.add_file(PathBuf::from("/test/b.ts"), false);
+        let edge = |kind, symbols: &[&str], via_barrel: Option<&str>| EdgeInfo {
+            kinds: vec![kind],
+            symbols: symbols.iter().map(|s| s.to_string()).collect(),
+            via_barrel: via_barrel.map(PathBuf::from),
+            ..Default::default()
+        };
+
+        graph.add_dependency_with_info(a, b, edge(ImportType::StaticImport, &["sum", "calc"], Some("/test/index.ts")));
+        graph.add_dependency_with_info(a, b, edge(ImportType::DynamicImport, &["calc"], Some("/test/index.ts")));
+        let info = graph.get_edge_info(a, b).unwrap();
+        assert_eq!(info.kinds, vec![ImportType::StaticImport, ImportType::DynamicImport]);
+        assert_eq!(info.symbols, vec!["calc", "sum"]);
+        assert!(info.has_kind(ImportType::DynamicImport) && !info.has_kind(ImportType::Require));
+        assert_eq!(info.via_barrel, Some(PathBuf::from("/test/index.ts")));
+
+        // Importing the file directly as well means it's no longer only reached through the barrel
+        graph.add_dependency_with_info(a, b, edge(ImportType::Require, &[], None));
+        assert_eq!(graph.get_edge_info(a, b).unwrap().via_barrel, None);
+
+        // An edge of unknown kind stays unknown
+        graph.add_dependency(a, b);
+        assert!(graph.get_edge_info(a, b).unwrap().kinds.is_empty());
+    }
 }
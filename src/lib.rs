@@ -13,8 +13,8 @@ pub mod barrel;
 #[path = "tests.rs"]
 mod tests;
 
-pub use graph::{DependencyGraph, FileId, FileNode};
-pub use parser::{ImportInfo, ImportType};
+pub use graph::{DependencyGraph, EdgeInfo, FileId, FileNode};
+pub use parser::{ImportInfo, ImportType, SourceLocation};
 pub use config::Config;
-pub use affected::{AffectedResult, DependencyPath};
+pub use affected::{AffectedResult, DependencyPath, ImportStep};
 pub use barrel::{BarrelAnalyzer};
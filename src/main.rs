@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, ExitCode};
 use clap::{Parser, Subcommand};
 use anyhow::Result;
@@ -7,9 +7,10 @@ use scopetest::config::Config;
 use scopetest::builder::GraphBuilder;
 use scopetest::cache::CacheManager;
 use scopetest::git::GitChangeDetector;
-use scopetest::affected::AffectedTestFinder;
-use scopetest::output::{OutputFormat, OutputFormatter};
+use scopetest::affected::{AffectedTestFinder, DependencyPath};
+use scopetest::output::{DoctorReport, FileDiagnostic, MissingImport, OutputFormat, OutputFormatter, PartialImport, PathStyle};
 use scopetest::barrel::{BarrelAnalyzer};
+use scopetest::parser::ImportType;
 
 #[derive(Parser)]
 #[command(name = "scopetest")]
@@ -63,6 +64,18 @@ enum Commands {
         /// Disable barrel import expansion (use barrel file as dependency instead of actual sources)
         #[arg(long)]
         no_barrel_expand: bool,
+
+        /// Don't select tests that only reach a change through type-only imports
+        #[arg(long)]
+        ignore_type_edges: bool,
+
+        /// Don't select tests that only reach a change through dynamic `import()`
+        #[arg(long)]
+        ignore_dynamic_imports: bool,
+
+        /// Print paths relative to the project root or the working directory: root, cwd
+        #[arg(long, value_name = "BASE")]
+        relative_to: Option<String>,
     },
 
     /// Explain why a test is affected by changes
@@ -89,6 +102,10 @@ enum Commands {
         /// Show all paths, not just the shortest
         #[arg(long)]
         all: bool,
+
+        /// Print paths relative to the project root or the working directory: root, cwd
+        #[arg(long, value_name = "BASE")]
+        relative_to: Option<String>,
     },
 
     /// Build or rebuild the dependency graph
@@ -96,6 +113,33 @@ enum Commands {
         /// Project root directory
         #[arg(short, long)]
         root: Option<PathBuf>,
+
+        /// Print paths relative to the project root or the working directory: root, cwd
+        #[arg(long, value_name = "BASE")]
+        relative_to: Option<String>,
+    },
+
+    /// Report files and imports that make test selection imprecise
+    Doctor {
+        /// Project root directory
+        #[arg(short, long)]
+        root: Option<PathBuf>,
+
+        /// Output format: text, json
+        #[arg(short, long, default_value = "text")]
+        format: String,
+
+        /// Exit non-zero when more imports than this fail to resolve (overrides maxUnresolvedImports)
+        #[arg(long)]
+        max_unresolved: Option<usize>,
+
+        /// Disable cache
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Print paths relative to the project root or the working directory: root, cwd
+        #[arg(long, value_name = "BASE")]
+        relative_to: Option<String>,
     },
 
     /// Analyze and rewrite barrel imports to direct imports
@@ -134,15 +178,22 @@ fn main() -> ExitCode {
 
     let result = match cli.command {
         Commands::Affected { 
-            base, since, format, sources, no_cache, root, exec, fail_fast, threshold, no_barrel_expand 
+            base, since, format, sources, no_cache, root, exec, fail_fast, threshold, no_barrel_expand, ignore_type_edges,
+            ignore_dynamic_imports, relative_to
         } => {
-            run_affected(base, since, format, sources, no_cache, root, exec, fail_fast, threshold, no_barrel_expand)
+            run_affected(
+                base, since, format, sources, no_cache, root, exec, fail_fast, threshold, no_barrel_expand, ignore_type_edges,
+                ignore_dynamic_imports, relative_to,
+            )
+        }
+        Commands::Why { test, base, since, root, no_cache, all, relative_to } => {
+            run_why(test, base, since, root, no_cache, all, relative_to)
         }
-        Commands::Why { test, base, since, root, no_cache, all } => {
-            run_why(test, base, since, root, no_cache, all)
+        Commands::Build { root, relative_to } => {
+            run_build(root, relative_to)
         }
-        Commands::Build { root } => {
-            run_build(root)
+        Commands::Doctor { root, format, max_unresolved, no_cache, relative_to } => {
+            run_doctor(root, format, max_unresolved, no_cache, relative_to)
         }
         Commands::Barrel { action } => {
             run_barrel(action)
@@ -162,23 +213,39 @@ fn get_root(root: Option<PathBuf>) -> PathBuf {
     root.unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
 }
 
-fn load_graph(root: &PathBuf, config: &Config, cache: &CacheManager, no_cache: bool) -> Result<scopetest::DependencyGraph> {
+fn parse_path_style(relative_to: Option<String>) -> Result<PathStyle> {
+    relative_to.map_or(Ok(PathStyle::Absolute), |base| base.parse().map_err(|e: String| anyhow::anyhow!(e)))
+}
+
+fn load_graph(builder: &GraphBuilder, config: &Config, cache: &CacheManager, no_cache: bool) -> Result<scopetest::DependencyGraph> {
     if !no_cache && config.cache_enabled {
         match cache.load() {
-            Ok(Some(g)) => Ok(g),
+            // Built with the same config and resolution files, so only files
+            // changed, added or deleted since the cache was written need work
+            Ok(Some(mut g)) if g.config_fingerprint() == builder.config_fingerprint() => {
+                let refreshed = builder.update_incremental(&mut g)?;
+                if refreshed > 0 {
+                    eprintln!("Refreshed {} changed files in the cached graph.", refreshed);
+                }
+                // Touched but unchanged files get their new stamps saved too,
+                // so they aren't rehashed on every run
+                if refreshed > 0 || g.is_dirty() {
+                    let _ = cache.save(&g);
+                }
+                Ok(g)
+            }
             _ => {
-                let builder = GraphBuilder::new(root.clone(), config.clone());
                 let g = builder.build()?;
                 let _ = cache.save(&g);
                 Ok(g)
             }
         }
     } else {
-        let builder = GraphBuilder::new(root.clone(), config.clone());
         Ok(builder.build()?)
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_affected(
     base: Option<String>,
     since: Option<String>,
@@ -190,13 +257,24 @@ fn run_affected(
     fail_fast: bool,
     threshold: Option<usize>,
     no_barrel_expand: bool,
+    ignore_type_edges: bool,
+    ignore_dynamic_imports: bool,
+    relative_to: Option<String>,
 ) -> Result<ExitCode> {
+    let path_style = parse_path_style(relative_to)?;
+    // Commands from --exec run in the root, so cwd-relative paths would point elsewhere
+    let path_style = if exec.is_some() && path_style == PathStyle::Cwd { PathStyle::Root } else { path_style };
     let root = get_root(root);
     let mut config = Config::load(&root)?;
     config.expand_barrels = !no_barrel_expand;
+    config.ignore_type_edges |= ignore_type_edges;
+    if ignore_dynamic_imports {
+        config.ignore_import_kinds.push(ImportType::DynamicImport);
+    }
     let cache = CacheManager::new(&root);
 
-    let graph = load_graph(&root, &config, &cache, no_cache)?;
+    let builder = GraphBuilder::new(root.clone(), config.clone());
+    let graph = load_graph(&builder, &config, &cache, no_cache)?;
 
     let git = GitChangeDetector::new(root.clone())?;
     let changes = if let Some(ref since_ref) = since {
@@ -207,9 +285,19 @@ fn run_affected(
     };
 
     // Find affected
-    let finder = AffectedTestFinder::new(&graph);
+    let finder = AffectedTestFinder::new(&graph)
+        .ignore_type_edges(config.ignore_type_edges)
+        .ignore_import_kinds(&config.ignore_import_kinds)
+        .parse_errors_affect_package(config.parse_errors_affect_package);
     let result = finder.find_affected(&changes);
     let (total_tests, total_sources) = finder.get_totals();
+    let mut diagnostics = graph.get_parse_diagnostics();
+    if let Some(base) = path_style.base(graph.root()) {
+        for (path, _) in &mut diagnostics {
+            *path = scopetest::output::relative_to(path, &base);
+        }
+    }
+    let parse_errors = FileDiagnostic::from_parse_diagnostics(&diagnostics);
 
     if let Some(max_tests) = threshold {
         if result.tests.len() > max_tests {
@@ -221,24 +309,28 @@ fn run_affected(
             let all_tests = graph.get_test_files();
             let files: Vec<PathBuf> = all_tests
                 .iter()
-                .filter_map(|&id| graph.get_file_path(id).map(|p| p.to_path_buf()))
+                .filter_map(|&id| graph.get_file_path(id))
                 .collect();
-            return run_with_files(&files, &format, exec, fail_fast, &root, total_tests, total_sources);
+            let files = path_style.apply(&files, graph.root());
+            return run_with_files(&files, &format, exec, fail_fast, &root, total_tests, total_sources, &parse_errors);
         }
     }
 
     let files = if sources { &result.sources } else { &result.tests };
-    run_with_files(files, &format, exec, fail_fast, &root, total_tests, total_sources)
+    let files = path_style.apply(files, graph.root());
+    run_with_files(&files, &format, exec, fail_fast, &root, total_tests, total_sources, &parse_errors)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_with_files(
     files: &[PathBuf],
     format: &str,
     exec: Option<String>,
     fail_fast: bool,
-    root: &PathBuf,
+    root: &Path,
     total_tests: usize,
     total_sources: usize,
+    parse_errors: &[FileDiagnostic],
 ) -> Result<ExitCode> {
     let output_format: OutputFormat = format.parse()
         .map_err(|e: String| anyhow::anyhow!(e))?;
@@ -251,6 +343,7 @@ fn run_with_files(
             &files_vec,
             total_tests,
             total_sources,
+            parse_errors,
         ),
         OutputFormat::List => OutputFormatter::format_list(files),
     };
@@ -272,7 +365,7 @@ fn run_with_files(
                 let status = Command::new("sh")
                     .arg("-c")
                     .arg(&cmd_str)
-                    .current_dir(&root)
+                    .current_dir(root)
                     .stdin(std::process::Stdio::inherit())
                     .stdout(std::process::Stdio::inherit())
                     .stderr(std::process::Stdio::inherit())
@@ -292,7 +385,7 @@ fn run_with_files(
             let status = Command::new("sh")
                 .arg("-c")
                 .arg(&cmd_str)
-                .current_dir(&root)
+                .current_dir(root)
                 .stdin(std::process::Stdio::inherit())
                 .stdout(std::process::Stdio::inherit())
                 .stderr(std::process::Stdio::inherit())
@@ -316,12 +409,15 @@ fn run_why(
     root: Option<PathBuf>,
     no_cache: bool,
     all: bool,
+    relative_to: Option<String>,
 ) -> Result<ExitCode> {
+    let path_style = parse_path_style(relative_to)?;
     let root = get_root(root);
     let config = Config::load(&root)?;
     let cache = CacheManager::new(&root);
 
-    let graph = load_graph(&root, &config, &cache, no_cache)?;
+    let builder = GraphBuilder::new(root.clone(), config.clone());
+    let graph = load_graph(&builder, &config, &cache, no_cache)?;
 
     let test_path = if test.is_absolute() {
         test
@@ -342,7 +438,10 @@ fn run_why(
         return Ok(ExitCode::SUCCESS);
     }
 
-    let finder = AffectedTestFinder::new(&graph);
+    let finder = AffectedTestFinder::new(&graph)
+        .ignore_type_edges(config.ignore_type_edges)
+        .ignore_import_kinds(&config.ignore_import_kinds)
+        .parse_errors_affect_package(config.parse_errors_affect_package);
 
     let affected = finder.find_affected(&changes);
     let test_canonical = std::fs::canonicalize(&test_path).unwrap_or(test_path.clone());
@@ -357,11 +456,21 @@ fn run_why(
         for f in &changed_files {
             eprintln!("  - {}", f.display());
         }
+        if !changes.packages.is_empty() {
+            eprintln!("\nChanged packages:");
+            for package in &changes.packages {
+                eprintln!("  - {}", package);
+            }
+        }
         return Ok(ExitCode::SUCCESS);
     }
 
     println!("Test '{}' IS affected by changes.\n", test_path.display());
 
+    // Steps are printed relative to the root unless another base was asked for
+    let display_root = path_style.base(graph.root()).unwrap_or_else(|| graph.root().to_path_buf());
+    let styled = |path: &DependencyPath| DependencyPath { chain: path_style.apply(&path.chain, graph.root()), ..path.clone() };
+
     if all {
         let paths = finder.find_all_paths_to_test(&test_path, &changes);
         if paths.is_empty() {
@@ -369,14 +478,20 @@ fn run_why(
         } else {
             println!("Dependency paths ({} found):\n", paths.len());
             for (i, path) in paths.iter().enumerate() {
-                println!("  {}. {}", i + 1, path.format());
+                println!("  {}. {}", i + 1, styled(path).format());
+                for step in &path.steps {
+                    println!("       {}", step.format(&display_root));
+                }
             }
         }
     } else {
         match finder.find_why(&test_path, &changes) {
             Some(path) => {
                 println!("Shortest dependency path:\n");
-                println!("  {}", path.format());
+                println!("  {}\n", styled(&path).format());
+                for step in &path.steps {
+                    println!("  {}", step.format(&display_root));
+                }
                 println!("\nUse --all to see all paths.");
             }
             None => {
@@ -388,7 +503,8 @@ fn run_why(
     Ok(ExitCode::SUCCESS)
 }
 
-fn run_build(root: Option<PathBuf>) -> Result<ExitCode> {
+fn run_build(root: Option<PathBuf>, relative_to: Option<String>) -> Result<ExitCode> {
+    let path_style = parse_path_style(relative_to)?;
     let root = get_root(root);
     let config = Config::load(&root)?;
     let cache = CacheManager::new(&root);
@@ -399,6 +515,26 @@ fn run_build(root: Option<PathBuf>) -> Result<ExitCode> {
     let graph = builder.build()?;
     
     eprintln!("Found {} files with {} dependencies", graph.file_count(), graph.edge_count());
+
+    let partial = graph.get_partial_imports();
+    if !partial.is_empty() {
+        eprintln!(
+            "{} partially analyzable imports (expanded to all matching files, selection is approximate):",
+            partial.len()
+        );
+        let files: Vec<PathBuf> = partial.iter().map(|(file, _)| file.clone()).collect();
+        for (file, (_, specifier)) in path_style.apply(&files, graph.root()).iter().zip(&partial) {
+            eprintln!("  {}: {}", file.display(), specifier);
+        }
+    }
+
+    let broken = graph.get_parse_diagnostics();
+    if !broken.is_empty() {
+        eprintln!(
+            "{} files parsed with errors (run `scopetest doctor` for details)",
+            broken.len()
+        );
+    }
     
     cache.save(&graph)?;
     eprintln!("Cache saved.");
@@ -406,6 +542,60 @@ fn run_build(root: Option<PathBuf>) -> Result<ExitCode> {
     Ok(ExitCode::SUCCESS)
 }
 
+fn run_doctor(
+    root: Option<PathBuf>,
+    format: String,
+    max_unresolved: Option<usize>,
+    no_cache: bool,
+    relative_to: Option<String>,
+) -> Result<ExitCode> {
+    let path_style = parse_path_style(relative_to)?;
+    let root = get_root(root);
+    let config = Config::load(&root)?;
+    let cache = CacheManager::new(&root);
+
+    let builder = GraphBuilder::new(root.clone(), config.clone());
+    let graph = load_graph(&builder, &config, &cache, no_cache)?;
+    let unresolved = graph.get_unresolved_imports();
+    let limit = max_unresolved.or(config.max_unresolved_imports);
+
+    let base = path_style.base(graph.root());
+    let styled = |path: &Path| base.as_ref().map_or_else(|| path.to_path_buf(), |base| scopetest::output::relative_to(path, base));
+    let mut diagnostics = graph.get_parse_diagnostics();
+    for (path, _) in &mut diagnostics {
+        *path = styled(path);
+    }
+    let mut missing = unresolved.clone();
+    for (path, _) in &mut missing {
+        *path = styled(path);
+    }
+
+    let report = DoctorReport {
+        parse_errors: FileDiagnostic::from_parse_diagnostics(&diagnostics),
+        partial_imports: graph
+            .get_partial_imports()
+            .into_iter()
+            .map(|(file, specifier)| PartialImport { file: styled(&file).to_string_lossy().to_string(), specifier })
+            .collect(),
+        unresolved_imports: MissingImport::from_unresolved(&missing),
+        config_notes: builder.config_notes(&unresolved),
+    };
+
+    match format.as_str() {
+        "text" => println!("{}", report.format_text()),
+        "json" => println!("{}", report.format_json()),
+        other => anyhow::bail!("Unknown format: {}. Use: text, json", other),
+    }
+
+    if let Some(limit) = limit {
+        if unresolved.len() > limit {
+            eprintln!("{} unresolved imports, more than the allowed {}", unresolved.len(), limit);
+            return Ok(ExitCode::FAILURE);
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
 
 fn run_barrel(action: BarrelAction) -> Result<ExitCode> {
     match action {
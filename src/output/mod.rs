@@ -1,6 +1,9 @@
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use serde::Serialize;
 
+use crate::graph::UnresolvedImport;
+use crate::parser::ParseDiagnostic;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OutputFormat {
     Paths,
@@ -21,6 +24,68 @@ impl std::str::FromStr for OutputFormat {
     }
 }
 
+/// How file paths are printed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PathStyle {
+    #[default]
+    Absolute,
+    /// Relative to the project root
+    Root,
+    /// Relative to the working directory
+    Cwd,
+}
+
+impl std::str::FromStr for PathStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "absolute" => Ok(PathStyle::Absolute),
+            "root" => Ok(PathStyle::Root),
+            "cwd" => Ok(PathStyle::Cwd),
+            _ => Err(format!("Unknown path style: {}. Use: absolute, root, cwd", s)),
+        }
+    }
+}
+
+impl PathStyle {
+    /// Directory printed paths are relative to, or `None` to print them as
+    /// they are. `root` must be canonical, like the paths the graph returns.
+    pub fn base(self, root: &Path) -> Option<PathBuf> {
+        match self {
+            PathStyle::Absolute => None,
+            PathStyle::Root => Some(root.to_path_buf()),
+            PathStyle::Cwd => std::env::current_dir().and_then(std::fs::canonicalize).ok(),
+        }
+    }
+
+    /// Rewrites absolute `paths` for printing.
+    pub fn apply(self, paths: &[PathBuf], root: &Path) -> Vec<PathBuf> {
+        match self.base(root) {
+            Some(base) => paths.iter().map(|path| relative_to(path, &base)).collect(),
+            None => paths.to_vec(),
+        }
+    }
+}
+
+/// `path` relative to the directory `base`, climbing out of it with `..` if needed.
+pub fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    if path.is_relative() {
+        return path.to_path_buf();
+    }
+    let path_components: Vec<_> = path.components().collect();
+    let base_components: Vec<_> = base.components().collect();
+    let common = path_components
+        .iter()
+        .zip(&base_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut relative: PathBuf = base_components[common..].iter().map(|_| Component::ParentDir).collect();
+    relative.extend(&path_components[common..]);
+    relative
+}
+
 #[derive(Debug, Serialize)]
 pub struct AffectedStats {
     pub total_tests: usize,
@@ -34,6 +99,131 @@ pub struct JsonOutput {
     pub tests: Vec<String>,
     pub sources: Vec<String>,
     pub stats: AffectedStats,
+    /// Files whose imports were only partially recovered, so selection may miss tests
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parse_errors: Vec<FileDiagnostic>,
+}
+
+/// A parse diagnostic flattened for reports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileDiagnostic {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+impl FileDiagnostic {
+    /// Flattens the per-file diagnostics returned by `DependencyGraph::get_parse_diagnostics`.
+    pub fn from_parse_diagnostics(files: &[(PathBuf, Vec<ParseDiagnostic>)]) -> Vec<Self> {
+        files
+            .iter()
+            .flat_map(|(path, errors)| {
+                errors.iter().map(move |e| FileDiagnostic {
+                    file: path.to_string_lossy().to_string(),
+                    line: e.location.line,
+                    column: e.location.column,
+                    message: e.message.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PartialImport {
+    pub file: String,
+    pub specifier: String,
+}
+
+/// An import that failed to resolve, flattened for reports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingImport {
+    pub file: String,
+    pub line: u32,
+    pub specifier: String,
+    pub reason: String,
+}
+
+impl MissingImport {
+    /// Flattens the imports returned by `DependencyGraph::get_unresolved_imports`.
+    pub fn from_unresolved(imports: &[(PathBuf, UnresolvedImport)]) -> Vec<Self> {
+        imports
+            .iter()
+            .map(|(path, import)| MissingImport {
+                file: path.to_string_lossy().to_string(),
+                line: import.location.line,
+                specifier: import.specifier.clone(),
+                reason: import.reason.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Everything that makes test selection less precise than it could be.
+#[derive(Debug, Default, Serialize)]
+pub struct DoctorReport {
+    pub parse_errors: Vec<FileDiagnostic>,
+    pub partial_imports: Vec<PartialImport>,
+    pub unresolved_imports: Vec<MissingImport>,
+    /// Missing tsconfig/workspace configuration behind the unresolved imports
+    pub config_notes: Vec<String>,
+}
+
+impl DoctorReport {
+    pub fn is_clean(&self) -> bool {
+        self.parse_errors.is_empty()
+            && self.partial_imports.is_empty()
+            && self.unresolved_imports.is_empty()
+            && self.config_notes.is_empty()
+    }
+
+    pub fn format_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    pub fn format_text(&self) -> String {
+        if self.is_clean() {
+            return "No problems found.".to_string();
+        }
+
+        let mut lines = Vec::new();
+        if !self.parse_errors.is_empty() {
+            lines.push(format!("{} parse errors (dependencies of these files may be incomplete):", self.parse_errors.len()));
+            for d in &self.parse_errors {
+                if d.line > 0 {
+                    lines.push(format!("  {}:{}:{}: {}", d.file, d.line, d.column, d.message));
+                } else {
+                    lines.push(format!("  {}: {}", d.file, d.message));
+                }
+            }
+        }
+        if !self.partial_imports.is_empty() {
+            lines.push(format!(
+                "{} partially analyzable imports (expanded to all matching files):",
+                self.partial_imports.len()
+            ));
+            for p in &self.partial_imports {
+                lines.push(format!("  {}: {}", p.file, p.specifier));
+            }
+        }
+        if !self.unresolved_imports.is_empty() {
+            lines.push(format!(
+                "{} unresolved imports (the files they point to are not dependencies):",
+                self.unresolved_imports.len()
+            ));
+            for m in &self.unresolved_imports {
+                lines.push(format!("  {}:{}: {} ({})", m.file, m.line, m.specifier, m.reason));
+            }
+        }
+        if !self.config_notes.is_empty() {
+            lines.push("Configuration:".to_string());
+            for note in &self.config_notes {
+                lines.push(format!("  {}", note));
+            }
+        }
+        lines.join("\n")
+    }
 }
 
 pub struct OutputFormatter;
@@ -53,6 +243,7 @@ impl OutputFormatter {
         sources: &[PathBuf],
         total_tests: usize,
         total_sources: usize,
+        parse_errors: &[FileDiagnostic],
     ) -> String {
         let output = JsonOutput {
             tests: tests.iter().filter_map(|p| p.to_str()).map(String::from).collect(),
@@ -63,6 +254,7 @@ impl OutputFormatter {
                 total_sources,
                 affected_sources: sources.len(),
             },
+            parse_errors: parse_errors.to_vec(),
         };
 
         serde_json::to_string_pretty(&output).unwrap_or_default()
@@ -104,6 +296,22 @@ mod tests {
         assert_eq!(pattern, "src/foo.spec.ts src/bar.test.ts");
     }
 
+    #[test]
+    fn test_relative_paths() {
+        let root = Path::new("/repo");
+        assert_eq!(relative_to(Path::new("/repo/src/a.spec.ts"), root), PathBuf::from("src/a.spec.ts"));
+        assert_eq!(
+            relative_to(Path::new("/repo/packages/lib/b.spec.ts"), Path::new("/repo/packages/app")),
+            PathBuf::from("../lib/b.spec.ts")
+        );
+
+        let tests = vec![PathBuf::from("/repo/src/a.spec.ts")];
+        assert_eq!(PathStyle::Absolute.apply(&tests, root), tests);
+        assert_eq!(PathStyle::Root.apply(&tests, root), vec![PathBuf::from("src/a.spec.ts")]);
+        assert_eq!("cwd".parse::<PathStyle>(), Ok(PathStyle::Cwd));
+        assert!("home".parse::<PathStyle>().is_err());
+    }
+
     #[test]
     fn test_format_list() {
         let files = vec![PathBuf::from("src/a.ts"), PathBuf::from("src/b.ts")];
@@ -119,4 +327,56 @@ mod tests {
         assert!(matches!("list".parse::<OutputFormat>(), Ok(OutputFormat::List)));
         assert!(matches!("json".parse::<OutputFormat>(), Ok(OutputFormat::Json)));
     }
+
+    #[test]
+    fn test_doctor_reports_unresolved_imports() {
+        use crate::parser::SourceLocation;
+
+        let unresolved = MissingImport::from_unresolved(&[(
+            PathBuf::from("/src/app.ts"),
+            UnresolvedImport {
+                specifier: "@/utils".to_string(),
+                location: SourceLocation { line: 2, column: 1, text: String::new() },
+                reason: "not a relative path, alias or package name".to_string(),
+            },
+        )]);
+        let report = DoctorReport {
+            unresolved_imports: unresolved,
+            config_notes: vec!["No tsconfig.json at the root".to_string()],
+            ..Default::default()
+        };
+
+        assert!(!report.is_clean());
+        assert_eq!(
+            report.format_text(),
+            "1 unresolved imports (the files they point to are not dependencies):\n  \
+             /src/app.ts:2: @/utils (not a relative path, alias or package name)\n\
+             Configuration:\n  No tsconfig.json at the root"
+        );
+        let v: serde_json::Value = serde_json::from_str(&report.format_json()).unwrap();
+        assert_eq!(v["unresolved_imports"][0]["specifier"], "@/utils");
+    }
+
+    #[test]
+    fn test_json_includes_parse_errors() {
+        use crate::parser::SourceLocation;
+
+        let diagnostics = FileDiagnostic::from_parse_diagnostics(&[(
+            PathBuf::from("/src/broken.ts"),
+            vec![ParseDiagnostic {
+                location: SourceLocation { line: 3, column: 7, text: String::new() },
+                message: "Unexpected token".to_string(),
+            }],
+        )]);
+        let json = OutputFormatter::format_json(&[], &[], 1, 1, &diagnostics);
+        let v: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(v["parse_errors"][0]["file"], "/src/broken.ts");
+        assert_eq!(v["parse_errors"][0]["line"], 3);
+
+        let report = DoctorReport { parse_errors: diagnostics, ..Default::default() };
+        assert_eq!(
+            report.format_text(),
+            "1 parse errors (dependencies of these files may be incomplete):\n  /src/broken.ts:3:7: Unexpected token"
+        );
+    }
 }
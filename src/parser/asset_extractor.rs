@@ -0,0 +1,188 @@
+use std::path::Path;
+use std::sync::OnceLock;
+use regex::Regex;
+
+use super::import_extractor::LineIndex;
+use super::{ImportInfo, ImportType, SourceLocation};
+
+pub const STYLE_EXTENSIONS: &[&str] = &[".css", ".scss", ".sass", ".less"];
+pub const GRAPHQL_EXTENSIONS: &[&str] = &[".graphql", ".gql"];
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| extensions.contains(&format!(".{}", ext).as_str()))
+        .unwrap_or(false)
+}
+
+pub fn is_stylesheet(path: &Path) -> bool {
+    has_extension(path, STYLE_EXTENSIONS)
+}
+
+pub fn is_graphql(path: &Path) -> bool {
+    has_extension(path, GRAPHQL_EXTENSIONS)
+}
+
+fn asset_import(source: &str, location: SourceLocation) -> ImportInfo {
+    ImportInfo {
+        source: source.to_string(),
+        import_type: ImportType::AssetImport,
+        named_imports: vec![],
+        is_namespace: true,
+        has_default: false,
+        is_type_only: false,
+        partial: None,
+        module_set: None,
+        location,
+    }
+}
+
+/// Extracts `@import`, `@use` and `@forward` targets from CSS, SCSS, Sass and Less.
+///
+/// Built-in modules (`sass:math`) and remote URLs are skipped since they
+/// never point at a project file.
+pub fn parse_stylesheet(source: &str) -> Vec<ImportInfo> {
+    static BLOCK_COMMENT: OnceLock<Regex> = OnceLock::new();
+    static AT_RULE: OnceLock<Regex> = OnceLock::new();
+    static QUOTED: OnceLock<Regex> = OnceLock::new();
+
+    let block_comment = BLOCK_COMMENT.get_or_init(|| Regex::new(r"(?s)/\*.*?\*/").unwrap());
+    let at_rule = AT_RULE.get_or_init(|| {
+        Regex::new(r#"@(import|use|forward)\s+([^;\n]+);?"#).unwrap()
+    });
+    let quoted = QUOTED.get_or_init(|| {
+        Regex::new(r#"(?:url\(\s*)?["']([^"']+)["']|url\(\s*([^)"'\s]+)\s*\)"#).unwrap()
+    });
+
+    // Blank out comments rather than removing them so line/column numbers still match
+    let without_comments = block_comment.replace_all(source, |caps: &regex::Captures| {
+        caps[0].chars().map(|c| if c == '\n' { '\n' } else { ' ' }).collect::<String>()
+    });
+    let mut imports = Vec::new();
+
+    for (index, line) in without_comments.lines().enumerate() {
+        if line.trim_start().starts_with("//") {
+            continue;
+        }
+
+        for rule in at_rule.captures_iter(line) {
+            let whole = rule.get(0).unwrap();
+            let mut location = SourceLocation::from_offsets(line, whole.start(), whole.end());
+            location.line += index as u32;
+
+            let keyword = &rule[1];
+            let specifiers = quoted.captures_iter(&rule[2])
+                .filter_map(|c| c.get(1).or_else(|| c.get(2)))
+                .map(|m| m.as_str());
+
+            // `@use 'x' with (...)` / `@forward 'x' show ...` only name one module;
+            // `@import 'a', 'b'` can list several
+            let specifiers: Vec<&str> = if keyword == "import" {
+                specifiers.collect()
+            } else {
+                specifiers.take(1).collect()
+            };
+
+            for specifier in specifiers {
+                if is_remote_or_builtin(specifier) {
+                    continue;
+                }
+                imports.push(asset_import(specifier, location.clone()));
+            }
+        }
+    }
+
+    imports
+}
+
+/// Extracts `#import "./fragment.graphql"` directives from GraphQL documents.
+pub fn parse_graphql(source: &str) -> Vec<ImportInfo> {
+    static IMPORT: OnceLock<Regex> = OnceLock::new();
+    let import = IMPORT.get_or_init(|| {
+        Regex::new(r#"(?m)^[ \t]*(#\s*import\s+["']([^"']+)["'])"#).unwrap()
+    });
+
+    let lines = LineIndex::new(source);
+    import.captures_iter(source)
+        .map(|c| {
+            let directive = c.get(1).unwrap();
+            let location = lines.location(directive.start(), directive.end());
+            asset_import(&c[2], location)
+        })
+        .collect()
+}
+
+fn is_remote_or_builtin(specifier: &str) -> bool {
+    specifier.starts_with("sass:")
+        || specifier.starts_with("http://")
+        || specifier.starts_with("https://")
+        || specifier.starts_with("//")
+        || specifier.starts_with("data:")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources(imports: &[ImportInfo]) -> Vec<&str> {
+        imports.iter().map(|i| i.source.as_str()).collect()
+    }
+
+    #[test]
+    fn test_scss_import_use_forward() {
+        let imports = parse_stylesheet(r#"
+            @use 'sass:math';
+            @use './variables' as vars;
+            @use "mixins" with ($primary: 'blue');
+            @forward 'src/list' hide list-reset;
+            @import 'reset', 'typography';
+            .button { width: math.div(10px, 2); }
+        "#);
+        assert_eq!(sources(&imports), vec!["./variables", "mixins", "src/list", "reset", "typography"]);
+        assert!(imports.iter().all(|i| i.import_type == ImportType::AssetImport));
+    }
+
+    #[test]
+    fn test_css_import_url() {
+        let imports = parse_stylesheet(r#"
+            @import url("./base.css");
+            @import url(theme.css);
+            @import "https://fonts.googleapis.com/css?family=Inter";
+        "#);
+        assert_eq!(sources(&imports), vec!["./base.css", "theme.css"]);
+    }
+
+    #[test]
+    fn test_commented_imports_ignored() {
+        let imports = parse_stylesheet(r#"
+            // @import 'old';
+            /* @import 'older'; */
+            @import 'current';
+        "#);
+        assert_eq!(sources(&imports), vec!["current"]);
+    }
+
+    #[test]
+    fn test_graphql_import() {
+        let imports = parse_graphql(r#"
+            #import "./UserFields.graphql"
+            # just a comment
+            query User { user { ...UserFields } }
+        "#);
+        assert_eq!(sources(&imports), vec!["./UserFields.graphql"]);
+    }
+
+    #[test]
+    fn test_stylesheet_locations() {
+        let imports = parse_stylesheet("/* header\n   comment */\n.a {}\n  @use './tokens';\n");
+        assert_eq!(imports[0].location, SourceLocation {
+            line: 4,
+            column: 3,
+            text: "@use './tokens';".to_string(),
+        });
+
+        let imports = parse_graphql("query A { a }\n#import \"./B.graphql\"\n");
+        assert_eq!(imports[0].location.line, 2);
+        assert_eq!(imports[0].location.text, "#import \"./B.graphql\"");
+    }
+}
@@ -1,17 +1,37 @@
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::OnceLock;
 use oxc_allocator::Allocator;
 use oxc_parser::Parser;
-use oxc_span::SourceType;
+use oxc_span::{GetSpan, SourceType, Span};
 use oxc_ast::ast::*;
+use serde::{Deserialize, Serialize};
+use oxc_ast_visit::{walk, Visit};
+use regex::Regex;
 
 use super::ParseError;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum ImportType {
     StaticImport,
     DynamicImport,
     Require,
     ReExport,
+    /// Module path passed to a jest/vitest mocking API (`jest.mock`, `vi.importActual`, ...)
+    Mock,
+    /// Vite `import.meta.glob(...)`
+    Glob,
+    /// webpack `require.context(...)`
+    Context,
+    /// Stylesheet `@import` / `@use` / `@forward` or GraphQL `#import`
+    AssetImport,
+    /// TypeScript `import fs = require('./fs')`
+    ImportEquals,
+    /// Import of a module that is then re-exported whole with TypeScript's `export = Foo`
+    ExportAssignment,
+    /// `/// <reference path="./globals.d.ts" />`; always relative to the file
+    TripleSlashReference,
 }
 
 #[derive(Debug, Clone)]
@@ -24,147 +44,599 @@ pub struct ImportInfo {
     pub is_namespace: bool,
     /// Whether this has a default import
     pub has_default: bool,
+    /// Whether this import is erased at runtime (`import type`, `export type ... from`,
+    /// or an import whose specifiers are all inline `type` specifiers)
+    pub is_type_only: bool,
+    /// Set when the specifier is only partially known at parse time
+    /// (e.g. `import(`./locales/${lang}.ts`)`); `source` then holds `prefix*suffix`
+    pub partial: Option<PartialSpecifier>,
+    /// Set for bundler APIs that pull in a whole set of modules
+    /// (`import.meta.glob`, `require.context`)
+    pub module_set: Option<ModuleSet>,
+    /// Where the import appears in the importing file
+    pub location: SourceLocation,
+}
+
+/// Position and text of the statement or call responsible for an import.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    /// 1-based line number
+    pub line: u32,
+    /// 1-based column, in characters
+    pub column: u32,
+    /// Source text with whitespace collapsed, truncated for very long calls
+    pub text: String,
+}
+
+const MAX_LOCATION_TEXT: usize = 120;
+
+impl SourceLocation {
+    /// Builds the location of `source[start..end]`. Use a [`LineIndex`] when
+    /// locating many ranges in the same source.
+    pub fn from_offsets(source: &str, start: usize, end: usize) -> Self {
+        LineIndex::new(source).location(start, end)
+    }
+
+    /// Moves a location found inside an embedded block (e.g. a `<script>` tag)
+    /// to its position in the enclosing file, given the block's own location.
+    pub fn offset_by(&mut self, block: &SourceLocation) {
+        if self.line == 1 {
+            self.column += block.column - 1;
+        }
+        self.line += block.line - 1;
+    }
+}
+
+/// Byte offsets where each line of a source starts, so locations can be
+/// found by binary search instead of rescanning the source every time.
+pub(crate) struct LineIndex<'s> {
+    source: &'s str,
+    line_starts: Vec<usize>,
+}
+
+impl<'s> LineIndex<'s> {
+    pub(crate) fn new(source: &'s str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { source, line_starts }
+    }
+
+    /// Builds the location of `source[start..end]`.
+    pub(crate) fn location(&self, start: usize, end: usize) -> SourceLocation {
+        let start = start.min(self.source.len());
+        let end = end.clamp(start, self.source.len());
+        let line = self.line_starts.partition_point(|&line_start| line_start <= start);
+        let column = self.source[self.line_starts[line - 1]..start].chars().count() as u32 + 1;
+
+        let mut text = self.source[start..end].split_whitespace().collect::<Vec<_>>().join(" ");
+        if text.chars().count() > MAX_LOCATION_TEXT {
+            text = text.chars().take(MAX_LOCATION_TEXT).collect::<String>() + "...";
+        }
+
+        SourceLocation { line: line as u32, column, text }
+    }
+}
+
+/// A set of modules selected by a bundler API rather than a single specifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModuleSet {
+    /// `import.meta.glob('./routes/*.tsx')`; patterns starting with `!` exclude matches
+    Glob { patterns: Vec<String> },
+    /// `require.context('./icons', true, /\.svg$/)`; `filter` is the regex source,
+    /// matched against `./`-prefixed paths relative to `directory`
+    Context { directory: String, recursive: bool, filter: Option<String> },
+}
+
+/// Static prefix and suffix of a specifier built from a template literal or
+/// string concatenation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialSpecifier {
+    pub prefix: String,
+    pub suffix: String,
+}
+
+impl PartialSpecifier {
+    /// Glob-style display form, e.g. `./locales/*.ts`.
+    pub fn pattern(&self) -> String {
+        format!("{}*{}", self.prefix, self.suffix)
+    }
+}
+
+fn extract_imports_from_program(program: &Program, lines: LineIndex) -> Vec<ImportInfo> {
+    let mut imports = reference_directives(program, &lines);
+    let mut collector = ImportCollector {
+        lines,
+        imports: Vec::new(),
+        module_bindings: HashMap::new(),
+        export_assignments: Vec::new(),
+    };
+    collector.visit_program(program);
+
+    // `export = Foo` may come before or after the import binding `Foo`; the
+    // import itself becomes the re-export rather than gaining a second edge
+    for name in std::mem::take(&mut collector.export_assignments) {
+        if let Some(&index) = collector.module_bindings.get(&name) {
+            let import = &mut collector.imports[index];
+            import.import_type = ImportType::ExportAssignment;
+            import.is_namespace = true; // consumers get the whole module
+        }
+    }
+
+    imports.extend(collector.imports);
+    imports
+}
+
+/// Extracts `/// <reference path="..." />` directives. TypeScript only honours
+/// them in the comments preceding the first statement.
+fn reference_directives(program: &Program, lines: &LineIndex) -> Vec<ImportInfo> {
+    static REFERENCE: OnceLock<Regex> = OnceLock::new();
+    let reference = REFERENCE.get_or_init(|| {
+        Regex::new(r#"^///\s*<reference\s+path\s*=\s*["']([^"']+)["']"#).unwrap()
+    });
+
+    let first_statement = program.body.first().map_or(u32::MAX, |s| s.span().start);
+
+    program.comments
+        .iter()
+        .filter(|c| c.is_line() && c.span.end <= first_statement)
+        .filter_map(|c| {
+            let text = &lines.source[c.span.start as usize..c.span.end as usize];
+            let path = reference.captures(text)?.get(1)?.as_str().to_string();
+            Some(ImportInfo {
+                source: path,
+                import_type: ImportType::TripleSlashReference,
+                named_imports: vec![],
+                is_namespace: true,
+                has_default: false,
+                is_type_only: false,
+                partial: None,
+                module_set: None,
+                location: lines.location(c.span.start as usize, c.span.end as usize),
+            })
+        })
+        .collect()
 }
 
-fn extract_imports_from_program(program: &Program) -> Vec<ImportInfo> {
-    let mut imports = Vec::new();
-
-    for stmt in &program.body {
-        match stmt {
-            Statement::ImportDeclaration(decl) => {
-                let mut named_imports = Vec::new();
-                let mut is_namespace = false;
-                let mut has_default = false;
-                
-                if let Some(specifiers) = &decl.specifiers {
-                    for spec in specifiers {
-                        match spec {
-                            ImportDeclarationSpecifier::ImportSpecifier(s) => {
-                                named_imports.push(s.imported.name().to_string());
-                            }
-                            ImportDeclarationSpecifier::ImportDefaultSpecifier(_) => {
-                                has_default = true;
-                            }
-                            ImportDeclarationSpecifier::ImportNamespaceSpecifier(_) => {
-                                is_namespace = true;
-                            }
-                        }
+/// Walks the whole AST so imports nested in functions, callbacks, class
+/// bodies or conditionals are found, not just top-level statements.
+struct ImportCollector<'s> {
+    lines: LineIndex<'s>,
+    imports: Vec<ImportInfo>,
+    /// Local names bound to a whole module (default, namespace or import-equals),
+    /// mapped to the index of the import that binds them
+    module_bindings: HashMap<String, usize>,
+    /// Identifiers exported with `export = name`
+    export_assignments: Vec<String>,
+}
+
+impl ImportCollector<'_> {
+    fn location(&self, span: Span) -> SourceLocation {
+        self.lines.location(span.start as usize, span.end as usize)
+    }
+}
+
+impl<'a> Visit<'a> for ImportCollector<'_> {
+    fn visit_import_declaration(&mut self, decl: &ImportDeclaration<'a>) {
+        let mut named_imports = Vec::new();
+        let mut is_namespace = false;
+        let mut has_default = false;
+
+        if let Some(specifiers) = &decl.specifiers {
+            for spec in specifiers {
+                match spec {
+                    ImportDeclarationSpecifier::ImportSpecifier(s) => {
+                        named_imports.push(s.imported.name().to_string());
+                    }
+                    ImportDeclarationSpecifier::ImportDefaultSpecifier(s) => {
+                        has_default = true;
+                        self.module_bindings.insert(s.local.name.to_string(), self.imports.len());
+                    }
+                    ImportDeclarationSpecifier::ImportNamespaceSpecifier(s) => {
+                        is_namespace = true;
+                        self.module_bindings.insert(s.local.name.to_string(), self.imports.len());
                     }
                 }
-                
-                imports.push(ImportInfo {
-                    source: decl.source.value.to_string(),
-                    import_type: ImportType::StaticImport,
-                    named_imports,
-                    is_namespace,
-                    has_default,
-                });
             }
-            Statement::ExportNamedDeclaration(decl) => {
-                if let Some(source) = &decl.source {
-                    let named_imports: Vec<String> = decl.specifiers
-                        .iter()
-                        .map(|s| s.local.name().to_string())
-                        .collect();
-                    
-                    imports.push(ImportInfo {
-                        source: source.value.to_string(),
-                        import_type: ImportType::ReExport,
-                        named_imports,
-                        is_namespace: false,
+        }
+
+        let is_type_only = decl.import_kind.is_type()
+            || decl.specifiers.as_ref().is_some_and(|specs| {
+                !specs.is_empty()
+                    && specs.iter().all(|spec| matches!(
+                        spec,
+                        ImportDeclarationSpecifier::ImportSpecifier(s) if s.import_kind.is_type()
+                    ))
+            });
+
+        self.imports.push(ImportInfo {
+            source: decl.source.value.to_string(),
+            import_type: ImportType::StaticImport,
+            named_imports,
+            is_namespace,
+            has_default,
+            is_type_only,
+            partial: None,
+            module_set: None,
+            location: self.location(decl.span),
+        });
+    }
+
+    fn visit_export_named_declaration(&mut self, decl: &ExportNamedDeclaration<'a>) {
+        if let Some(source) = &decl.source {
+            let named_imports: Vec<String> = decl.specifiers
+                .iter()
+                .map(|s| s.local.name().to_string())
+                .collect();
+
+            let is_type_only = decl.export_kind.is_type()
+                || (!decl.specifiers.is_empty()
+                    && decl.specifiers.iter().all(|s| s.export_kind.is_type()));
+
+            self.imports.push(ImportInfo {
+                source: source.value.to_string(),
+                import_type: ImportType::ReExport,
+                named_imports,
+                is_namespace: false,
+                has_default: false,
+                is_type_only,
+                partial: None,
+                module_set: None,
+                location: self.location(decl.span),
+            });
+        }
+        walk::walk_export_named_declaration(self, decl);
+    }
+
+    fn visit_export_all_declaration(&mut self, decl: &ExportAllDeclaration<'a>) {
+        self.imports.push(ImportInfo {
+            source: decl.source.value.to_string(),
+            import_type: ImportType::ReExport,
+            named_imports: vec![],
+            is_namespace: true, // export * is like namespace
+            has_default: false,
+            is_type_only: decl.export_kind.is_type(),
+            partial: None,
+            module_set: None,
+            location: self.location(decl.span),
+        });
+    }
+
+    fn visit_ts_import_equals_declaration(&mut self, decl: &TSImportEqualsDeclaration<'a>) {
+        if let TSModuleReference::ExternalModuleReference(reference) = &decl.module_reference {
+            let source = reference.expression.value.to_string();
+            self.module_bindings.insert(decl.id.name.to_string(), self.imports.len());
+            self.imports.push(ImportInfo {
+                source,
+                import_type: ImportType::ImportEquals,
+                named_imports: vec![],
+                is_namespace: true, // binds the whole module like require
+                has_default: false,
+                is_type_only: decl.import_kind.is_type(),
+                partial: None,
+                module_set: None,
+                location: self.location(decl.span),
+            });
+        }
+    }
+
+    fn visit_ts_export_assignment(&mut self, assignment: &TSExportAssignment<'a>) {
+        if let Expression::Identifier(ident) = &assignment.expression {
+            self.export_assignments.push(ident.name.to_string());
+        }
+        walk::walk_ts_export_assignment(self, assignment);
+    }
+
+    fn visit_import_expression(&mut self, expr: &ImportExpression<'a>) {
+        if let Some((source, partial)) = specifier_from_expression(&expr.source) {
+            self.imports.push(ImportInfo {
+                source,
+                import_type: ImportType::DynamicImport,
+                named_imports: vec![],
+                is_namespace: true, // dynamic imports are like namespace
+                has_default: false,
+                is_type_only: false,
+                partial,
+                module_set: None,
+                location: self.location(expr.span),
+            });
+        }
+        walk::walk_import_expression(self, expr);
+    }
+
+    fn visit_call_expression(&mut self, call: &CallExpression<'a>) {
+        if let Expression::Identifier(ident) = &call.callee {
+            if ident.name == "require" {
+                let specifier = call.arguments.first()
+                    .and_then(|arg| arg.as_expression())
+                    .and_then(specifier_from_expression);
+                if let Some((source, partial)) = specifier {
+                    self.imports.push(ImportInfo {
+                        source,
+                        import_type: ImportType::Require,
+                        named_imports: vec![],
+                        is_namespace: true, // require is like namespace
                         has_default: false,
+                        is_type_only: false,
+                        partial,
+                        module_set: None,
+                        location: self.location(call.span),
                     });
                 }
             }
-            Statement::ExportAllDeclaration(decl) => {
-                imports.push(ImportInfo {
-                    source: decl.source.value.to_string(),
-                    import_type: ImportType::ReExport,
+        }
+        if let Some(module_set) = module_set_from_call(call) {
+            let (source, import_type) = match &module_set {
+                ModuleSet::Glob { patterns } => (patterns.join(", "), ImportType::Glob),
+                ModuleSet::Context { directory, .. } => (directory.clone(), ImportType::Context),
+            };
+            self.imports.push(ImportInfo {
+                source,
+                import_type,
+                named_imports: vec![],
+                is_namespace: true,
+                has_default: false,
+                is_type_only: false,
+                partial: None,
+                module_set: Some(module_set),
+                location: self.location(call.span),
+            });
+        }
+        if is_mock_call(&call.callee) {
+            if let Some(Argument::StringLiteral(lit)) = call.arguments.first() {
+                self.imports.push(ImportInfo {
+                    source: lit.value.to_string(),
+                    import_type: ImportType::Mock,
                     named_imports: vec![],
-                    is_namespace: true, // export * is like namespace
+                    is_namespace: true, // mocks replace the whole module
                     has_default: false,
+                    is_type_only: false,
+                    partial: None,
+                    module_set: None,
+                    location: self.location(call.span),
                 });
             }
-            Statement::ExpressionStatement(expr_stmt) => {
-                extract_imports_from_expression(&expr_stmt.expression, &mut imports);
-            }
-            Statement::VariableDeclaration(var_decl) => {
-                for decl in &var_decl.declarations {
-                    if let Some(init) = &decl.init {
-                        extract_imports_from_expression(init, &mut imports);
-                    }
-                }
+        }
+        walk::walk_call_expression(self, call);
+    }
+}
+
+/// Recognises `import.meta.glob(...)` / `import.meta.globEager(...)` and
+/// `require.context(...)` calls with statically known arguments.
+fn module_set_from_call(call: &CallExpression) -> Option<ModuleSet> {
+    let Expression::StaticMemberExpression(member) = &call.callee else {
+        return None;
+    };
+    let method = member.property.name.as_str();
+
+    match &member.object {
+        Expression::MetaProperty(meta)
+            if meta.meta.name == "import" && meta.property.name == "meta"
+                && matches!(method, "glob" | "globEager") =>
+        {
+            let patterns = match call.arguments.first()? {
+                Argument::ArrayExpression(arr) => arr.elements
+                    .iter()
+                    .filter_map(|el| el.as_expression())
+                    .filter_map(static_string)
+                    .collect(),
+                arg => vec![static_string(arg.as_expression()?)?],
+            };
+            if patterns.is_empty() {
+                return None;
             }
-            _ => {}
+            Some(ModuleSet::Glob { patterns })
+        }
+        Expression::Identifier(ident) if ident.name == "require" && method == "context" => {
+            let directory = static_string(call.arguments.first()?.as_expression()?)?;
+            let recursive = match call.arguments.get(1) {
+                Some(Argument::BooleanLiteral(b)) => b.value,
+                _ => true,
+            };
+            let filter = match call.arguments.get(2) {
+                Some(Argument::RegExpLiteral(re)) => re.raw.as_ref().and_then(|raw| regex_source(raw)),
+                _ => None,
+            };
+            Some(ModuleSet::Context { directory, recursive, filter })
         }
+        _ => None,
     }
+}
 
-    imports
+/// Converts a JS regex literal (`/\.svg$/i`) into a pattern the `regex` crate accepts.
+fn regex_source(raw: &str) -> Option<String> {
+    let end = raw.rfind('/')?;
+    let body = raw.get(1..end)?;
+    let flags = &raw[end + 1..];
+    Some(if flags.contains('i') { format!("(?i){}", body) } else { body.to_string() })
+}
+
+fn static_string(expr: &Expression) -> Option<String> {
+    match specifier_from_expression(expr)? {
+        (source, None) => Some(source),
+        _ => None,
+    }
+}
+
+/// Evaluates a specifier expression as far as it is statically known.
+///
+/// Returns the full specifier for string literals, template literals without
+/// substitutions and concatenations of those. Specifiers with a dynamic part
+/// return their `prefix*suffix` form together with the static parts; those
+/// without a static prefix can't be anchored anywhere and are skipped.
+fn specifier_from_expression(expr: &Expression) -> Option<(String, Option<PartialSpecifier>)> {
+    let mut parts = Vec::new();
+    collect_specifier_parts(expr, &mut parts);
+
+    if parts.iter().all(Option::is_some) {
+        return Some((parts.into_iter().flatten().collect(), None));
+    }
+
+    let prefix: String = parts.iter().map_while(|p| p.as_deref()).collect();
+    let mut suffix_parts: Vec<&str> = parts.iter().rev().map_while(|p| p.as_deref()).collect();
+    suffix_parts.reverse();
+    let suffix = suffix_parts.concat();
+
+    if prefix.is_empty() {
+        return None;
+    }
+
+    let partial = PartialSpecifier { prefix, suffix };
+    Some((partial.pattern(), Some(partial)))
 }
 
-fn extract_imports_from_expression(expr: &Expression, imports: &mut Vec<ImportInfo>) {
+/// Flattens a specifier expression into static (`Some`) and dynamic (`None`) parts.
+fn collect_specifier_parts(expr: &Expression, parts: &mut Vec<Option<String>>) {
     match expr {
-        Expression::ImportExpression(import_expr) => {
-            if let Expression::StringLiteral(lit) = &import_expr.source {
-                imports.push(ImportInfo {
-                    source: lit.value.to_string(),
-                    import_type: ImportType::DynamicImport,
-                    named_imports: vec![],
-                    is_namespace: true, // dynamic imports are like namespace
-                    has_default: false,
-                });
-            }
-        }
-        Expression::CallExpression(call_expr) => {
-            if let Expression::Identifier(ident) = &call_expr.callee {
-                if ident.name == "require" {
-                    if let Some(arg) = call_expr.arguments.first() {
-                        if let Argument::StringLiteral(lit) = arg {
-                            imports.push(ImportInfo {
-                                source: lit.value.to_string(),
-                                import_type: ImportType::Require,
-                                named_imports: vec![],
-                                is_namespace: true, // require is like namespace
-                                has_default: false,
-                            });
-                        }
-                    }
-                }
-            }
-            for arg in &call_expr.arguments {
-                if let Argument::SpreadElement(spread) = arg {
-                    extract_imports_from_expression(&spread.argument, imports);
+        Expression::StringLiteral(lit) => parts.push(Some(lit.value.to_string())),
+        Expression::TemplateLiteral(tpl) => {
+            for (i, quasi) in tpl.quasis.iter().enumerate() {
+                let text = quasi.value.cooked.as_ref().unwrap_or(&quasi.value.raw);
+                parts.push(Some(text.to_string()));
+                if let Some(expr) = tpl.expressions.get(i) {
+                    collect_specifier_parts(expr, parts);
                 }
             }
         }
-        Expression::AwaitExpression(await_expr) => {
-            extract_imports_from_expression(&await_expr.argument, imports);
+        Expression::BinaryExpression(bin) if bin.operator == BinaryOperator::Addition => {
+            collect_specifier_parts(&bin.left, parts);
+            collect_specifier_parts(&bin.right, parts);
         }
-        _ => {}
+        Expression::ParenthesizedExpression(paren) => {
+            collect_specifier_parts(&paren.expression, parts);
+        }
+        _ => parts.push(None),
+    }
+}
+
+const JEST_MOCK_METHODS: &[&str] = &[
+    "mock", "unmock", "doMock", "dontMock", "setMock",
+    "requireActual", "requireMock", "createMockFromModule", "genMockFromModule",
+];
+
+const VITEST_MOCK_METHODS: &[&str] = &[
+    "mock", "unmock", "doMock", "doUnmock", "importActual", "importMock",
+];
+
+/// Matches `jest.<method>(...)` and `vi.<method>(...)` calls whose first
+/// argument is a module path.
+fn is_mock_call(callee: &Expression) -> bool {
+    let Expression::StaticMemberExpression(member) = callee else {
+        return false;
+    };
+    let Expression::Identifier(object) = &member.object else {
+        return false;
+    };
+    let method = member.property.name.as_str();
+    match object.name.as_str() {
+        "jest" => JEST_MOCK_METHODS.contains(&method),
+        "vi" | "vitest" => VITEST_MOCK_METHODS.contains(&method),
+        _ => false,
     }
 }
 
-pub fn parse_file(path: &Path) -> Result<Vec<ImportInfo>, ParseError> {
+/// Imports of a file together with the syntax errors oxc recovered from.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedFile {
+    pub imports: Vec<ImportInfo>,
+    pub errors: Vec<ParseDiagnostic>,
+}
+
+/// A syntax error (or read failure) reported while parsing a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseDiagnostic {
+    /// Where the error was reported; line 0 when it isn't tied to a position
+    pub location: SourceLocation,
+    pub message: String,
+}
+
+pub fn parse_file(path: &Path) -> Result<ParsedFile, ParseError> {
     let source_text = std::fs::read_to_string(path)?;
-    parse_source(&source_text, path)
+    Ok(parse_source(&source_text, path))
 }
 
-pub fn parse_source(source: &str, path: &Path) -> Result<Vec<ImportInfo>, ParseError> {
-    let allocator = Allocator::default();
+pub fn parse_source(source: &str, path: &Path) -> ParsedFile {
     let source_type = SourceType::from_path(path).unwrap_or_default();
+    parse_source_recovering(source, source_type)
+}
+
+/// Parses in oxc's recovery mode: imports are extracted from whatever program
+/// oxc recovered, and every syntax error is kept as a diagnostic. When oxc
+/// gives up on the file entirely, imports are scanned lexically instead.
+pub fn parse_source_recovering(source: &str, source_type: SourceType) -> ParsedFile {
+    let allocator = Allocator::default();
     let parser = Parser::new(&allocator, source, source_type);
     let result = parser.parse();
-    
-    if result.panicked {
-        return Err(ParseError::SyntaxError(
-            result.errors.iter()
-                .map(|e| e.to_string())
-                .collect::<Vec<_>>()
-                .join(", ")
-        ));
-    }
-    
-    Ok(extract_imports_from_program(&result.program))
+    let lines = LineIndex::new(source);
+
+    let errors = result.errors.iter()
+        .map(|e| {
+            let offset = e.labels.as_ref()
+                .and_then(|labels| labels.first())
+                .map(|label| (label.offset(), label.offset() + label.len()));
+            let location = match offset {
+                Some((start, end)) => lines.location(start, end),
+                None => SourceLocation::default(),
+            };
+            ParseDiagnostic { location, message: e.message.to_string() }
+        })
+        .collect();
+
+    let imports = if result.panicked {
+        scan_imports(&lines)
+    } else {
+        extract_imports_from_program(&result.program, lines)
+    };
+    ParsedFile { imports, errors }
+}
+
+/// Best-effort scan for `import`/`export ... from`, `import()` and `require()`
+/// with literal specifiers, used when there is no AST to walk. Every match is
+/// treated as a namespace import since the bindings can't be trusted.
+fn scan_imports(lines: &LineIndex) -> Vec<ImportInfo> {
+    static COMMENT: OnceLock<Regex> = OnceLock::new();
+    static IMPORT: OnceLock<Regex> = OnceLock::new();
+
+    let comment = COMMENT.get_or_init(|| Regex::new(r"(?s)/\*.*?\*/|//[^\n]*").unwrap());
+    let import = IMPORT.get_or_init(|| {
+        Regex::new(concat!(
+            r#"\b(?:(?P<static>import)\s+(?P<type>type\s+)?(?:[\w$*{},\s]+?\s+from\s*)?"#,
+            r#"|(?P<export>export)\s+(?P<export_type>type\s+)?[\w$*{},\s]*?\s*from\s*"#,
+            r#"|(?P<call>import|require)\s*\(\s*)["'](?P<source>[^"'\n]+)["']"#,
+        )).unwrap()
+    });
+
+    // Blank out comments rather than removing them so offsets still match
+    let without_comments = comment.replace_all(lines.source, |caps: &regex::Captures| {
+        caps[0].chars().map(|c| if c == '\n' { '\n' } else { ' ' }).collect::<String>()
+    });
+
+    import.captures_iter(&without_comments)
+        .map(|caps| {
+            let whole = caps.get(0).unwrap();
+            let import_type = if caps.name("static").is_some() {
+                ImportType::StaticImport
+            } else if caps.name("export").is_some() {
+                ImportType::ReExport
+            } else if &caps["call"] == "require" {
+                ImportType::Require
+            } else {
+                ImportType::DynamicImport
+            };
+
+            ImportInfo {
+                source: caps["source"].to_string(),
+                import_type,
+                named_imports: vec![],
+                is_namespace: true,
+                has_default: false,
+                is_type_only: caps.name("type").or_else(|| caps.name("export_type")).is_some(),
+                partial: None,
+                module_set: None,
+                location: lines.location(whole.start(), whole.end() + 1),
+            }
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -173,7 +645,7 @@ mod tests {
     use std::path::PathBuf;
 
     fn parse_ts(source: &str) -> Vec<ImportInfo> {
-        parse_source(source, &PathBuf::from("test.ts")).unwrap()
+        parse_source(source, &PathBuf::from("test.ts")).imports
     }
 
     #[test]
@@ -248,4 +720,367 @@ mod tests {
         assert_eq!(imports[0].source, "..");
         assert_eq!(imports[0].import_type, ImportType::StaticImport);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_require_in_function_body() {
+        let imports = parse_ts(r#"
+            function load() {
+                const foo = require('./foo');
+                return foo;
+            }
+        "#);
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].source, "./foo");
+        assert_eq!(imports[0].import_type, ImportType::Require);
+    }
+
+    #[test]
+    fn test_dynamic_import_in_arrow_callback() {
+        let imports = parse_ts(r#"const Page = lazy(() => import('./Page'));"#);
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].source, "./Page");
+        assert_eq!(imports[0].import_type, ImportType::DynamicImport);
+    }
+
+    #[test]
+    fn test_require_in_if_block() {
+        let imports = parse_ts(r#"
+            if (process.env.NODE_ENV === 'test') {
+                require('./setup');
+            } else {
+                module.exports = require('./prod');
+            }
+        "#);
+        let sources: Vec<_> = imports.iter().map(|i| i.source.as_str()).collect();
+        assert_eq!(sources, vec!["./setup", "./prod"]);
+    }
+
+    #[test]
+    fn test_import_in_class_method() {
+        let imports = parse_ts(r#"
+            class Loader {
+                async load() {
+                    const { a } = await import('./a');
+                    return a;
+                }
+                static sync() {
+                    return require('./b');
+                }
+            }
+        "#);
+        let sources: Vec<_> = imports.iter().map(|i| i.source.as_str()).collect();
+        assert_eq!(sources, vec!["./a", "./b"]);
+    }
+
+    #[test]
+    fn test_require_in_conditional_expression() {
+        let imports = parse_ts(r#"const impl = isNode ? require('./node') : require('./browser');"#);
+        let sources: Vec<_> = imports.iter().map(|i| i.source.as_str()).collect();
+        assert_eq!(sources, vec!["./node", "./browser"]);
+    }
+
+    #[test]
+    fn test_imports_in_object_literal() {
+        let imports = parse_ts(r#"
+            export const routes = {
+                home: () => import('./Home'),
+                about: { component: require('./About') },
+            };
+        "#);
+        let sources: Vec<_> = imports.iter().map(|i| i.source.as_str()).collect();
+        assert_eq!(sources, vec!["./Home", "./About"]);
+    }
+
+    #[test]
+    fn test_require_in_template_literal() {
+        let imports = parse_ts(r#"const msg = `version: ${require('./version').value}`;"#);
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].source, "./version");
+    }
+
+    #[test]
+    fn test_nested_imports_in_tsx() {
+        let imports = parse_source(
+            r#"
+            export default function App() {
+                const Modal = React.lazy(() => import('./Modal'));
+                return <Suspense>{cond && <Modal />}</Suspense>;
+            }
+            "#,
+            &PathBuf::from("App.tsx"),
+        ).imports;
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].source, "./Modal");
+    }
+
+    #[test]
+    fn test_non_literal_require_ignored() {
+        let imports = parse_ts(r#"const m = require(name); obj.require('./x');"#);
+        assert!(imports.is_empty());
+    }
+
+    #[test]
+    fn test_jest_mock_calls() {
+        let imports = parse_ts(r#"
+            jest.mock('../api/client');
+            jest.mock('./logger', () => ({ log: jest.fn() }));
+            const actual = jest.requireActual('./utils');
+        "#);
+        let sources: Vec<_> = imports.iter().map(|i| i.source.as_str()).collect();
+        assert_eq!(sources, vec!["../api/client", "./logger", "./utils"]);
+        assert!(imports.iter().all(|i| i.import_type == ImportType::Mock));
+    }
+
+    #[test]
+    fn test_vitest_mock_calls() {
+        let imports = parse_ts(r#"
+            vi.mock('./store');
+            vi.mock('./db', async () => {
+                const mod = await vi.importActual('./db');
+                return { ...mod, connect: vi.fn() };
+            });
+        "#);
+        let sources: Vec<_> = imports.iter().map(|i| i.source.as_str()).collect();
+        assert_eq!(sources, vec!["./store", "./db", "./db"]);
+        assert!(imports.iter().all(|i| i.import_type == ImportType::Mock));
+    }
+
+    #[test]
+    fn test_unrelated_member_calls_not_mocks() {
+        let imports = parse_ts(r#"
+            jest.fn();
+            jest.spyOn(obj, 'method');
+            other.mock('./x');
+        "#);
+        assert!(imports.is_empty());
+    }
+
+    #[test]
+    fn test_type_only_imports() {
+        let imports = parse_ts(r#"
+            import type { A } from './a';
+            import { type B } from './b';
+            import { type C, D } from './c';
+            import E from './e';
+            import './side-effect';
+        "#);
+        let flags: Vec<_> = imports.iter().map(|i| (i.source.as_str(), i.is_type_only)).collect();
+        assert_eq!(flags, vec![
+            ("./a", true),
+            ("./b", true),
+            ("./c", false),
+            ("./e", false),
+            ("./side-effect", false),
+        ]);
+    }
+
+    #[test]
+    fn test_type_only_re_exports() {
+        let imports = parse_ts(r#"
+            export type { A } from './a';
+            export { type B } from './b';
+            export type * from './c';
+            export { D } from './d';
+            export * from './e';
+        "#);
+        let flags: Vec<_> = imports.iter().map(|i| (i.source.as_str(), i.is_type_only)).collect();
+        assert_eq!(flags, vec![
+            ("./a", true),
+            ("./b", true),
+            ("./c", true),
+            ("./d", false),
+            ("./e", false),
+        ]);
+    }
+
+    #[test]
+    fn test_template_literal_dynamic_import() {
+        let imports = parse_ts("const m = import(`./locales/${lang}.ts`);");
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].source, "./locales/*.ts");
+        assert_eq!(imports[0].import_type, ImportType::DynamicImport);
+        assert_eq!(imports[0].partial, Some(PartialSpecifier {
+            prefix: "./locales/".to_string(),
+            suffix: ".ts".to_string(),
+        }));
+    }
+
+    #[test]
+    fn test_concatenated_require() {
+        let imports = parse_ts(r#"const handler = require('./handlers/' + name);"#);
+        assert_eq!(imports.len(), 1);
+        assert_eq!(imports[0].source, "./handlers/*");
+        assert_eq!(imports[0].import_type, ImportType::Require);
+        assert_eq!(imports[0].partial.as_ref().unwrap().prefix, "./handlers/");
+        assert_eq!(imports[0].partial.as_ref().unwrap().suffix, "");
+    }
+
+    #[test]
+    fn test_fully_static_template_and_concatenation() {
+        let imports = parse_ts(r#"
+            import(`./a`);
+            require('./b' + '.js');
+        "#);
+        let sources: Vec<_> = imports.iter().map(|i| i.source.as_str()).collect();
+        assert_eq!(sources, vec!["./a", "./b.js"]);
+        assert!(imports.iter().all(|i| i.partial.is_none()));
+    }
+
+    #[test]
+    fn test_dynamic_without_static_prefix_skipped() {
+        let imports = parse_ts("import(`${base}/page.ts`); require(name + '.js');");
+        assert!(imports.is_empty());
+    }
+
+    #[test]
+    fn test_import_meta_glob() {
+        let imports = parse_ts(r#"
+            const routes = import.meta.glob('./routes/*.tsx');
+            const pages = import.meta.glob(['./pages/**/*.ts', '!./pages/**/*.spec.ts'], { eager: true });
+        "#);
+        assert_eq!(imports.len(), 2);
+        assert_eq!(imports[0].import_type, ImportType::Glob);
+        assert_eq!(imports[0].module_set, Some(ModuleSet::Glob {
+            patterns: vec!["./routes/*.tsx".to_string()],
+        }));
+        assert_eq!(imports[1].module_set, Some(ModuleSet::Glob {
+            patterns: vec!["./pages/**/*.ts".to_string(), "!./pages/**/*.spec.ts".to_string()],
+        }));
+    }
+
+    #[test]
+    fn test_require_context() {
+        let imports = parse_ts(r#"
+            const icons = require.context('./icons', true, /\.svg$/);
+            const flat = require.context('./flat', false);
+            const all = require.context('./all');
+        "#);
+        assert_eq!(imports.len(), 3);
+        assert!(imports.iter().all(|i| i.import_type == ImportType::Context));
+        assert_eq!(imports[0].module_set, Some(ModuleSet::Context {
+            directory: "./icons".to_string(),
+            recursive: true,
+            filter: Some(r"\.svg$".to_string()),
+        }));
+        assert_eq!(imports[1].module_set, Some(ModuleSet::Context {
+            directory: "./flat".to_string(),
+            recursive: false,
+            filter: None,
+        }));
+        assert_eq!(imports[2].module_set, Some(ModuleSet::Context {
+            directory: "./all".to_string(),
+            recursive: true,
+            filter: None,
+        }));
+    }
+
+    #[test]
+    fn test_import_locations() {
+        let imports = parse_ts("import { a } from './a';\n\nfunction load() {\n    return require('./b');\n}\nimport {\n    c,\n    d,\n} from './c';\n");
+        assert_eq!(imports.len(), 3);
+
+        assert_eq!(imports[0].location, SourceLocation {
+            line: 1,
+            column: 1,
+            text: "import { a } from './a';".to_string(),
+        });
+        assert_eq!(imports[1].location, SourceLocation {
+            line: 4,
+            column: 12,
+            text: "require('./b')".to_string(),
+        });
+        assert_eq!(imports[2].location.line, 6);
+        assert_eq!(imports[2].location.text, "import { c, d, } from './c';");
+    }
+
+    #[test]
+    fn test_line_index_boundaries() {
+        let lines = LineIndex::new("ab\n\nçd\n");
+        let at = |offset| {
+            let location = lines.location(offset, offset);
+            (location.line, location.column)
+        };
+
+        assert_eq!(at(0), (1, 1));
+        assert_eq!(at(2), (1, 3));
+        assert_eq!(at(3), (2, 1));
+        assert_eq!(at(6), (3, 2));
+        assert_eq!(at(8), (4, 1));
+        assert_eq!(at(100), (4, 1));
+    }
+
+    #[test]
+    fn test_long_location_text_truncated() {
+        let factory = "x".repeat(200);
+        let imports = parse_ts(&format!("jest.mock('./a', () => '{}');", factory));
+        assert!(imports[0].location.text.starts_with("jest.mock('./a'"));
+        assert!(imports[0].location.text.ends_with("..."));
+    }
+
+    #[test]
+    fn test_recovers_imports_from_broken_file() {
+        let parsed = parse_source_recovering(
+            "import { a } from './a';\nconst x = ;\nimport { b } from './b';\n",
+            SourceType::ts(),
+        );
+        assert!(!parsed.errors.is_empty());
+        assert_eq!(parsed.errors[0].location.line, 2);
+        let sources: Vec<_> = parsed.imports.iter().map(|i| i.source.as_str()).collect();
+        assert!(sources.contains(&"./a"));
+    }
+
+    #[test]
+    fn test_scan_imports_when_oxc_gives_up() {
+        let parsed = parse_source_recovering(
+            "// import './commented';\nimport type { T } from './types';\nexport * from './b';\nconst lazy = () => import('./c');\nconst d = require(\"./d\");\nconst x = ;\n",
+            SourceType::ts(),
+        );
+        let found: Vec<_> = parsed.imports.iter()
+            .map(|i| (i.source.as_str(), i.import_type, i.is_type_only))
+            .collect();
+        assert_eq!(found, vec![
+            ("./types", ImportType::StaticImport, true),
+            ("./b", ImportType::ReExport, false),
+            ("./c", ImportType::DynamicImport, false),
+            ("./d", ImportType::Require, false),
+        ]);
+        assert_eq!(parsed.imports[0].location.line, 2);
+    }
+
+    #[test]
+    fn test_import_equals_and_export_assignment() {
+        let imports = parse_ts(r#"
+            import fs = require('./fs');
+            import type Types = require('./types');
+            import Alias = Namespace.Inner;
+            export = fs;
+        "#);
+        let found: Vec<_> = imports.iter()
+            .map(|i| (i.source.as_str(), i.import_type, i.is_type_only))
+            .collect();
+        assert_eq!(found, vec![
+            ("./fs", ImportType::ExportAssignment, false),
+            ("./types", ImportType::ImportEquals, true),
+        ]);
+        assert_eq!(imports[0].location.text, "import fs = require('./fs');");
+    }
+
+    #[test]
+    fn test_triple_slash_references() {
+        let imports = parse_ts(r#"/// <reference path="./globals.d.ts" />
+/// <reference types="node" />
+/// <reference path='legacy/shim.ts'/>
+import { a } from './a';
+/// <reference path="./ignored.d.ts" />
+"#);
+        let found: Vec<_> = imports.iter()
+            .map(|i| (i.source.as_str(), i.import_type))
+            .collect();
+        assert_eq!(found, vec![
+            ("./globals.d.ts", ImportType::TripleSlashReference),
+            ("legacy/shim.ts", ImportType::TripleSlashReference),
+            ("./a", ImportType::StaticImport),
+        ]);
+        assert_eq!(imports[1].location.line, 3);
+    }
+}
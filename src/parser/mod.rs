@@ -1,8 +1,15 @@
+mod asset_extractor;
 mod import_extractor;
+mod sfc_extractor;
 
-pub use import_extractor::{ImportInfo, ImportType, parse_file};
+pub use asset_extractor::{is_graphql, is_stylesheet, GRAPHQL_EXTENSIONS, STYLE_EXTENSIONS};
+pub use import_extractor::{
+    ImportInfo, ImportType, ModuleSet, ParseDiagnostic, ParsedFile, PartialSpecifier, SourceLocation,
+};
+pub use sfc_extractor::{is_single_file_component, SFC_EXTENSIONS};
 
 use std::path::Path;
+use oxc_span::SourceType;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -13,7 +20,7 @@ pub enum ParseError {
     SyntaxError(String),
 }
 
-pub const SUPPORTED_EXTENSIONS: &[&str] = &[".ts", ".tsx", ".js", ".jsx", ".mjs", ".cjs"];
+pub const SUPPORTED_EXTENSIONS: &[&str] = &[".ts", ".tsx", ".js", ".jsx", ".mjs", ".cjs", ".mts", ".cts"];
 
 pub fn is_supported_extension(path: &Path) -> bool {
     path.extension()
@@ -24,3 +31,36 @@ pub fn is_supported_extension(path: &Path) -> bool {
         })
         .unwrap_or(false)
 }
+
+/// Extracts the imports of any file in the graph: JS/TS through oxc, Vue/Svelte/Astro
+/// components through their script blocks, stylesheets and GraphQL documents
+/// through their own directives. Other assets (JSON, SVG, ...)
+/// have no dependencies of their own.
+pub fn parse_file(path: &Path) -> Result<Vec<ImportInfo>, ParseError> {
+    parse_file_with_diagnostics(path).map(|parsed| parsed.imports)
+}
+
+/// Like [`parse_file`], but keeps the syntax errors oxc recovered from instead
+/// of dropping them. Only fails if the file can't be read.
+pub fn parse_file_with_diagnostics(path: &Path) -> Result<ParsedFile, ParseError> {
+    if is_single_file_component(path) {
+        let source = std::fs::read_to_string(path)?;
+        return Ok(sfc_extractor::parse_sfc(&source, path));
+    }
+
+    if is_stylesheet(path) {
+        let source = std::fs::read_to_string(path)?;
+        return Ok(ParsedFile { imports: asset_extractor::parse_stylesheet(&source), errors: vec![] });
+    }
+
+    if is_graphql(path) {
+        let source = std::fs::read_to_string(path)?;
+        return Ok(ParsedFile { imports: asset_extractor::parse_graphql(&source), errors: vec![] });
+    }
+
+    if SourceType::from_path(path).is_err() {
+        return Ok(ParsedFile::default());
+    }
+
+    import_extractor::parse_file(path)
+}
@@ -0,0 +1,221 @@
+use std::path::Path;
+use std::sync::OnceLock;
+use oxc_span::SourceType;
+use regex::Regex;
+
+use super::asset_extractor::parse_stylesheet;
+use super::import_extractor::parse_source_recovering;
+use super::{ImportInfo, ImportType, ParsedFile, SourceLocation};
+
+pub const SFC_EXTENSIONS: &[&str] = &[".vue", ".svelte", ".astro"];
+
+pub fn is_single_file_component(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| SFC_EXTENSIONS.contains(&format!(".{}", ext).as_str()))
+        .unwrap_or(false)
+}
+
+/// Extracts imports from a Vue, Svelte or Astro component.
+///
+/// Every `<script>` block (including `<script setup>` and Svelte's
+/// `context="module"`) is parsed with oxc according to its `lang`, Astro
+/// frontmatter is parsed as TypeScript, `<script src>` counts as an import and
+/// `<style>` blocks go through the stylesheet extractor. Syntax errors in a
+/// block are reported with positions in the component file.
+pub fn parse_sfc(source: &str, path: &Path) -> ParsedFile {
+    static FRONTMATTER: OnceLock<Regex> = OnceLock::new();
+    static SCRIPT: OnceLock<Regex> = OnceLock::new();
+    static STYLE: OnceLock<Regex> = OnceLock::new();
+
+    let frontmatter = FRONTMATTER.get_or_init(|| Regex::new(r"(?s)\A\s*---\r?\n(.*?)\r?\n---").unwrap());
+    let script = SCRIPT.get_or_init(|| Regex::new(r"(?is)<script\b([^>]*)>(.*?)</script\s*>").unwrap());
+    let style = STYLE.get_or_init(|| Regex::new(r"(?is)<style\b[^>]*>(.*?)</style\s*>").unwrap());
+
+    let is_astro = path.extension().and_then(|e| e.to_str()) == Some("astro");
+    let mut parsed = ParsedFile::default();
+
+    if is_astro {
+        if let Some(block) = frontmatter.captures(source) {
+            let content = block.get(1).unwrap();
+            let found = parse_source_recovering(content.as_str(), SourceType::ts());
+            relocate_into(&mut parsed, found, source, content.start());
+        }
+    }
+
+    for block in script.captures_iter(source) {
+        let attrs = &block[1];
+        let content = block.get(2).unwrap();
+
+        if let Some(src) = attribute(attrs, Attribute::Src) {
+            let tag = block.get(0).unwrap();
+            parsed.imports.push(ImportInfo {
+                source: src,
+                import_type: ImportType::StaticImport,
+                named_imports: vec![],
+                is_namespace: true,
+                has_default: false,
+                is_type_only: false,
+                partial: None,
+                module_set: None,
+                location: SourceLocation::from_offsets(source, tag.start(), content.start()),
+            });
+            continue;
+        }
+
+        let source_type = match attribute(attrs, Attribute::Lang).as_deref() {
+            Some("ts") => SourceType::ts(),
+            Some("tsx") => SourceType::tsx(),
+            Some("jsx") => SourceType::jsx(),
+            // Astro client scripts are always processed as TypeScript
+            _ if is_astro => SourceType::ts(),
+            _ => SourceType::mjs(),
+        };
+        let found = parse_source_recovering(content.as_str(), source_type);
+        relocate_into(&mut parsed, found, source, content.start());
+    }
+
+    for block in style.captures_iter(source) {
+        let content = block.get(1).unwrap();
+        let found = ParsedFile { imports: parse_stylesheet(content.as_str()), errors: vec![] };
+        relocate_into(&mut parsed, found, source, content.start());
+    }
+
+    parsed
+}
+
+/// Shifts locations of imports and errors found in a block starting at `offset`
+/// so they point into the component file, and appends them to `parsed`.
+fn relocate_into(parsed: &mut ParsedFile, found: ParsedFile, source: &str, offset: usize) {
+    let block = SourceLocation::from_offsets(source, offset, offset);
+    for mut import in found.imports {
+        import.location.offset_by(&block);
+        parsed.imports.push(import);
+    }
+    for mut error in found.errors {
+        // Errors without a position stay unpositioned
+        if error.location.line > 0 {
+            error.location.offset_by(&block);
+        }
+        parsed.errors.push(error);
+    }
+}
+
+/// Script tag attributes the extractor reads.
+#[derive(Clone, Copy)]
+enum Attribute {
+    Lang,
+    Src,
+}
+
+/// Reads a (possibly unquoted) attribute value from a tag's attribute list.
+fn attribute(attrs: &str, which: Attribute) -> Option<String> {
+    static LANG: OnceLock<Regex> = OnceLock::new();
+    static SRC: OnceLock<Regex> = OnceLock::new();
+
+    let (cell, name) = match which {
+        Attribute::Lang => (&LANG, "lang"),
+        Attribute::Src => (&SRC, "src"),
+    };
+    let re = cell.get_or_init(|| {
+        Regex::new(&format!(r#"(?i)(?:^|\s){}\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+))"#, name)).unwrap()
+    });
+    let caps = re.captures(attrs)?;
+    caps.get(1).or_else(|| caps.get(2)).or_else(|| caps.get(3)).map(|m| m.as_str().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources(imports: &[ImportInfo]) -> Vec<&str> {
+        imports.iter().map(|i| i.source.as_str()).collect()
+    }
+
+    #[test]
+    fn test_vue_script_setup_ts() {
+        let imports = parse_sfc(r#"
+<template>
+  <Button :label="label" />
+</template>
+
+<script lang="ts">
+import { defineComponent } from 'vue';
+export default defineComponent({ name: 'Card' });
+</script>
+
+<script setup lang="ts">
+import Button from './Button.vue';
+import type { Props } from './types';
+const props = defineProps<Props>();
+</script>
+
+<style lang="scss" scoped>
+@use './tokens';
+</style>
+"#, Path::new("Card.vue")).imports;
+
+        assert_eq!(sources(&imports), vec!["vue", "./Button.vue", "./types", "./tokens"]);
+        assert!(imports[2].is_type_only);
+        assert_eq!(imports[3].import_type, ImportType::AssetImport);
+    }
+
+    #[test]
+    fn test_svelte_module_and_instance_scripts() {
+        let imports = parse_sfc(r#"
+<script context="module">
+  export const preload = () => import('./data');
+</script>
+
+<script>
+  import Header from './Header.svelte';
+  export let title;
+</script>
+
+<Header {title} />
+"#, Path::new("Page.svelte")).imports;
+
+        assert_eq!(sources(&imports), vec!["./data", "./Header.svelte"]);
+    }
+
+    #[test]
+    fn test_astro_frontmatter() {
+        let imports = parse_sfc(r#"---
+import Layout from '../layouts/Layout.astro';
+import { getPosts } from '../lib/posts';
+const posts: Post[] = await getPosts();
+---
+<Layout>
+  <script>
+    import '../scripts/analytics';
+  </script>
+</Layout>
+"#, Path::new("index.astro")).imports;
+
+        assert_eq!(sources(&imports), vec!["../layouts/Layout.astro", "../lib/posts", "../scripts/analytics"]);
+    }
+
+    #[test]
+    fn test_script_src() {
+        let imports = parse_sfc(r#"<script src="./logic.ts" lang="ts"></script>"#, Path::new("Widget.vue")).imports;
+        assert_eq!(sources(&imports), vec!["./logic.ts"]);
+    }
+
+    #[test]
+    fn test_locations_point_into_component() {
+        let source = "<template><div /></template>\n<script setup lang=\"ts\">\nimport A from './A.vue';\n</script>\n";
+        let imports = parse_sfc(source, Path::new("Card.vue")).imports;
+        assert_eq!(imports[0].location.line, 3);
+        assert_eq!(imports[0].location.column, 1);
+        assert_eq!(imports[0].location.text, "import A from './A.vue';");
+    }
+
+    #[test]
+    fn test_broken_script_keeps_imports_and_reports_error() {
+        let source = "<template />\n<script setup lang=\"ts\">\nimport A from './A.vue';\nconst x = ;\n</script>\n";
+        let parsed = parse_sfc(source, Path::new("Card.vue"));
+        assert_eq!(sources(&parsed.imports), vec!["./A.vue"]);
+        assert_eq!(parsed.errors.len(), 1);
+        assert_eq!(parsed.errors[0].location.line, 4);
+    }
+}
@@ -1,82 +1,220 @@
+mod module_mapper;
+mod package_json;
+mod tsconfig;
+mod workspace;
+
+pub use module_mapper::ModuleMapper;
+pub use package_json::DEFAULT_CONDITIONS;
+pub use tsconfig::TsConfig;
+
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use ignore::WalkBuilder;
 use thiserror::Error;
 
+use crate::parser::{ImportInfo, ModuleSet, PartialSpecifier};
+
 #[derive(Error, Debug)]
 pub enum ResolveError {
     #[error("Could not resolve import: {0}")]
     NotFound(String),
+    /// A package from outside the project (npm, node builtins, URLs), which
+    /// has no node in the graph by design
+    #[error("External module: {0}")]
+    External(String),
     #[error("Failed to read tsconfig: {0}")]
     ConfigError(String),
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct ResolverConfig {
-    pub base_url: Option<PathBuf>,
-    pub paths: HashMap<String, Vec<String>>,
+    /// Used for files with no tsconfig.json of their own above them
+    pub tsconfig: TsConfig,
     pub extensions: Vec<String>,
+    /// package.json `exports`/`imports` conditions, in order of preference
+    pub conditions: Vec<String>,
+    /// React Native style platform suffixes (`ios` for `Button.ios.tsx`)
+    pub platforms: Vec<String>,
+    /// Files matching these (relative to the root) are never expansion candidates
+    pub ignore_patterns: Vec<glob::Pattern>,
 }
 
 pub struct PathResolver {
     config: ResolverConfig,
     root: PathBuf,
+    /// Nearest tsconfig for each directory looked up so far (`None`: no config above it)
+    tsconfigs: Mutex<HashMap<PathBuf, Option<Arc<TsConfig>>>>,
+    /// Workspace package name -> package directory
+    workspace_packages: HashMap<String, PathBuf>,
+    /// jest/vitest mappings applied before regular resolution
+    mapper: ModuleMapper,
 }
 
 impl PathResolver {
     pub fn new(root: PathBuf) -> Self {
         Self {
             config: ResolverConfig {
-                base_url: None,
-                paths: HashMap::new(),
+                tsconfig: TsConfig::default(),
                 extensions: vec![
                     ".ts".to_string(),
                     ".tsx".to_string(),
                     ".js".to_string(),
                     ".jsx".to_string(),
+                    ".mts".to_string(),
+                    ".cts".to_string(),
+                    ".mjs".to_string(),
+                    ".cjs".to_string(),
+                    ".d.ts".to_string(),
+                    ".json".to_string(),
                 ],
+                conditions: DEFAULT_CONDITIONS.iter().map(|c| c.to_string()).collect(),
+                platforms: Vec::new(),
+                ignore_patterns: Vec::new(),
             },
             root,
+            tsconfigs: Mutex::new(HashMap::new()),
+            workspace_packages: HashMap::new(),
+            mapper: ModuleMapper::default(),
         }
     }
 
-    pub fn load_tsconfig(&mut self, tsconfig_path: &Path) -> Result<(), ResolveError> {
-        let content = std::fs::read_to_string(tsconfig_path)
-            .map_err(|e| ResolveError::ConfigError(e.to_string()))?;
-        
-        let json: serde_json::Value = serde_json::from_str(&content)
-            .map_err(|e| ResolveError::ConfigError(e.to_string()))?;
-        
-        if let Some(compiler_options) = json.get("compilerOptions") {
-            if let Some(base_url) = compiler_options.get("baseUrl").and_then(|v| v.as_str()) {
-                let tsconfig_dir = tsconfig_path.parent().unwrap_or(Path::new("."));
-                self.config.base_url = Some(tsconfig_dir.join(base_url));
+    /// Appends extensions to probe after the built-in ones (e.g. `.vue`, `.svelte`).
+    pub fn add_extensions(&mut self, extensions: &[String]) {
+        for ext in extensions {
+            if !self.config.extensions.contains(ext) {
+                self.config.extensions.push(ext.clone());
             }
-            
-            if let Some(paths) = compiler_options.get("paths").and_then(|v| v.as_object()) {
-                for (pattern, targets) in paths {
-                    if let Some(targets_arr) = targets.as_array() {
-                        let resolved_targets: Vec<String> = targets_arr
-                            .iter()
-                            .filter_map(|t| t.as_str().map(String::from))
-                            .collect();
-                        self.config.paths.insert(pattern.clone(), resolved_targets);
-                    }
-                }
+        }
+    }
+
+    /// Replaces the package.json conditions, tried in the given order.
+    pub fn set_conditions(&mut self, conditions: &[String]) {
+        self.config.conditions = conditions.to_vec();
+    }
+
+    /// Sets the platform suffixes whose variants an import fans out to.
+    pub fn set_platforms(&mut self, platforms: &[String]) {
+        self.config.platforms = platforms.to_vec();
+    }
+
+    /// Sets the configured ignore globs, so partial specifiers and globs only
+    /// expand to files that file discovery would also pick up.
+    pub fn set_ignore_patterns(&mut self, patterns: &[String]) {
+        self.config.ignore_patterns = patterns.iter().filter_map(|p| glob::Pattern::new(p).ok()).collect();
+    }
+
+    /// Files under `dir` (down to `max_depth`), walked like project files are
+    /// discovered and without those matching the ignore patterns.
+    fn walk_files(&self, dir: &Path, max_depth: Option<usize>) -> Vec<PathBuf> {
+        project_walker(dir)
+            .max_depth(max_depth)
+            .build()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+            .map(|e| e.into_path())
+            .filter(|path| {
+                let relative = path.strip_prefix(&self.root).unwrap_or(path);
+                !self.config.ignore_patterns.iter().any(|p| p.matches_path(relative))
+            })
+            .collect()
+    }
+
+    /// Reads the workspace manifests under the root so workspace packages
+    /// resolve without `node_modules`. Returns the number of packages found.
+    pub fn load_workspaces(&mut self) -> usize {
+        self.workspace_packages = workspace::discover_packages(&self.root);
+        self.workspace_packages.len()
+    }
+
+    /// Applies jest/vitest module mappings (see [`ModuleMapper`]) before
+    /// regular resolution.
+    pub fn set_module_mapper(&mut self, mapper: ModuleMapper) {
+        self.mapper = mapper;
+    }
+
+    /// Loads the project-wide tsconfig, used for files without a nearer one.
+    /// Projects it `references` are registered for their directories, so a
+    /// config like `packages/a/tsconfig.lib.json` applies to that package.
+    pub fn load_tsconfig(&mut self, tsconfig_path: &Path) -> Result<(), ResolveError> {
+        let config = TsConfig::load(tsconfig_path)?;
+
+        let mut tsconfigs = self.tsconfigs.lock().unwrap();
+        for reference in &config.references {
+            if let (Ok(referenced), Some(dir)) = (TsConfig::load(reference), reference.parent()) {
+                tsconfigs.insert(dir.to_path_buf(), Some(Arc::new(referenced)));
             }
         }
-        
+        drop(tsconfigs);
+
+        self.config.tsconfig = config;
         Ok(())
     }
 
+    /// The tsconfig.json nearest to `from` within the root, loaded once per
+    /// directory.
+    fn tsconfig_for(&self, from: &Path) -> Option<Arc<TsConfig>> {
+        let mut tsconfigs = self.tsconfigs.lock().unwrap();
+        let mut visited = Vec::new();
+        let mut found = None;
+
+        for dir in from.ancestors().skip(1).take_while(|dir| dir.starts_with(&self.root)) {
+            if let Some(cached) = tsconfigs.get(dir) {
+                found = cached.clone();
+                break;
+            }
+            visited.push(dir.to_path_buf());
+
+            let candidate = dir.join("tsconfig.json");
+            // A broken config is skipped in favour of the next one up
+            if let Some(config) = candidate.is_file().then(|| TsConfig::load(&candidate).ok()).flatten() {
+                found = Some(Arc::new(config));
+                break;
+            }
+        }
+
+        for dir in visited {
+            tsconfigs.insert(dir, found.clone());
+        }
+        found
+    }
+
     pub fn resolve(&self, from: &Path, import_path: &str) -> Result<PathBuf, ResolveError> {
+        // Like jest, a mapped specifier is resolved through its mapping; unlike
+        // jest, a mapping that leads nowhere falls back to regular resolution
+        for target in self.mapper.map(import_path) {
+            if let Ok(resolved) = self.resolve_mapped(from, &target) {
+                return Ok(resolved);
+            }
+        }
+
+        self.resolve_unmapped(from, import_path)
+    }
+
+    /// Mapping targets are absolute, relative to the root, or module names.
+    fn resolve_mapped(&self, from: &Path, target: &str) -> Result<PathBuf, ResolveError> {
+        if Path::new(target).is_absolute() {
+            self.resolve_with_extensions(&normalize_path(Path::new(target)))
+        } else if target.starts_with('.') {
+            self.resolve_with_extensions(&normalize_path(&self.root.join(target)))
+        } else {
+            self.resolve_unmapped(from, target)
+        }
+    }
+
+    fn resolve_unmapped(&self, from: &Path, import_path: &str) -> Result<PathBuf, ResolveError> {
         if import_path.starts_with('.') || import_path.starts_with('/') {
             let from_dir = from.parent().unwrap_or(Path::new("."));
             let base_path = from_dir.join(import_path);
-            let normalized = self.normalize_path(&base_path);
+            let normalized = normalize_path(&base_path);
             return self.resolve_with_extensions(&normalized);
         }
 
-        if let Some(resolved) = self.resolve_alias(import_path) {
+        if import_path.starts_with('#') {
+            return self.resolve_package_import(from, import_path);
+        }
+
+        if let Some(resolved) = self.resolve_alias(from, import_path) {
             return Ok(resolved);
         }
 
@@ -84,11 +222,331 @@ impl PathResolver {
             return Ok(resolved);
         }
 
-        Err(ResolveError::NotFound(format!("External module: {}", import_path)))
+        if let Some(resolved) = self.resolve_in_module_directories(from, import_path) {
+            return Ok(resolved);
+        }
+
+        Err(self.unresolved_bare_specifier(from, import_path))
+    }
+
+    /// Tells a bare specifier that names something in the project, such as an
+    /// alias or workspace package whose target is missing, from a package
+    /// that lives outside of it.
+    fn unresolved_bare_specifier(&self, from: &Path, import_path: &str) -> ResolveError {
+        let (package_name, _) = self.parse_package_import(import_path);
+        if self.workspace_packages.contains_key(&package_name) {
+            return ResolveError::NotFound(format!("workspace package {} has no such module", package_name));
+        }
+        let nearest = self.tsconfig_for(from);
+        let tsconfig = nearest.as_deref().unwrap_or(&self.config.tsconfig);
+        if !tsconfig.path_candidates(import_path).is_empty() {
+            return ResolveError::NotFound("matches tsconfig paths, but none of the targets exist".to_string());
+        }
+        // `node:fs`, `virtual:icons` and bare builtins like `fs/promises`
+        let first_segment = import_path.split('/').next().unwrap_or("");
+        if import_path.contains(':') || NODE_BUILTINS.contains(&first_segment) {
+            return ResolveError::External(import_path.to_string());
+        }
+        // `~/utils/x` or a baseUrl-style `utils/y` is only a package if the
+        // project says so
+        if self.is_declared_dependency(from, &package_name) || self.is_installed_package(from, &package_name) {
+            return ResolveError::External(import_path.to_string());
+        }
+        ResolveError::NotFound("not a relative path, alias or declared package".to_string())
+    }
+
+    /// Whether a package.json between `from` and the root lists
+    /// `package_name` in any of its dependency fields.
+    fn is_declared_dependency(&self, from: &Path, package_name: &str) -> bool {
+        from.ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(&self.root))
+            .filter_map(read_package_json)
+            .any(|manifest| {
+                ["dependencies", "devDependencies", "peerDependencies", "optionalDependencies"]
+                    .iter()
+                    .any(|field| manifest.get(field).and_then(|deps| deps.get(package_name)).is_some())
+            })
+    }
+
+    /// Whether `package_name` is installed in a `node_modules` between `from`
+    /// and the root.
+    fn is_installed_package(&self, from: &Path, package_name: &str) -> bool {
+        is_external_specifier(package_name)
+            && from.ancestors()
+                .skip(1)
+                .take_while(|dir| dir.starts_with(&self.root))
+                .any(|dir| dir.join("node_modules").join(package_name).is_dir())
+    }
+
+    /// Looks for a bare specifier in jest `moduleDirectories` (e.g. `src`) of
+    /// every directory from the importing file up to the root.
+    fn resolve_in_module_directories(&self, from: &Path, import_path: &str) -> Option<PathBuf> {
+        if self.mapper.module_directories.is_empty() {
+            return None;
+        }
+        from.ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(&self.root))
+            .flat_map(|dir| self.mapper.module_directories.iter().map(move |m| dir.join(m).join(import_path)))
+            .find_map(|candidate| self.resolve_with_extensions(&candidate).ok())
+    }
+
+    /// Manual mock of a package in the `__mocks__` folder of a jest root,
+    /// which `jest.mock('pkg')` picks up automatically.
+    pub fn resolve_manual_mock(&self, specifier: &str) -> Option<PathBuf> {
+        if specifier.starts_with('.') || specifier.starts_with('/') {
+            return None;
+        }
+        let roots = if self.mapper.roots.is_empty() { std::slice::from_ref(&self.root) } else { &self.mapper.roots[..] };
+        roots.iter()
+            .find_map(|root| self.resolve_with_extensions(&root.join("__mocks__").join(specifier)).ok())
+    }
+
+    /// Resolves a private `#specifier` through the `imports` field of the
+    /// package.json enclosing `from`.
+    fn resolve_package_import(&self, from: &Path, import_path: &str) -> Result<PathBuf, ResolveError> {
+        let not_found = || ResolveError::NotFound("no matching entry in package.json imports".to_string());
+
+        let package_dir = from.ancestors()
+            .skip(1)
+            .find(|dir| dir.join("package.json").is_file())
+            .ok_or_else(not_found)?;
+        let manifest = read_package_json(package_dir).ok_or_else(not_found)?;
+        let targets = manifest.get("imports")
+            .map(|imports| package_json::resolve_imports(imports, import_path, &self.config.conditions))
+            .unwrap_or_default();
+
+        targets.iter()
+            .find_map(|target| {
+                if target.starts_with("./") {
+                    self.resolve_with_extensions(&normalize_path(&package_dir.join(target))).ok()
+                } else if target.starts_with('#') {
+                    // A target can't be another private import
+                    None
+                } else {
+                    // `"#dep": "some-package"` maps onto a dependency
+                    self.resolve(from, target).ok()
+                }
+            })
+            .ok_or_else(not_found)
+    }
+
+    /// Resolves a stylesheet `@import`/`@use` or GraphQL `#import` relative to the
+    /// importing file, following Sass conventions: bare names are relative,
+    /// extensions are optional and partials may be prefixed with `_`.
+    pub fn resolve_asset_import(&self, from: &Path, import_path: &str) -> Result<PathBuf, ResolveError> {
+        // `sass:math`, `~bootstrap/...` and load-path imports come from packages
+        if import_path.contains(':') || import_path.starts_with('~') {
+            return Err(ResolveError::External(import_path.to_string()));
+        }
+
+        let from_dir = from.parent().unwrap_or(Path::new("."));
+        let base = normalize_path(&from_dir.join(import_path));
+
+        if base.is_file() {
+            return Ok(base);
+        }
+
+        let dir = base.parent().unwrap_or(Path::new("."));
+        let name = base.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let style_extensions = crate::parser::STYLE_EXTENSIONS;
+
+        for ext in style_extensions {
+            for candidate in [format!("{}{}", name, ext), format!("_{}{}", name, ext)] {
+                let path = dir.join(candidate);
+                if path.is_file() {
+                    return Ok(path);
+                }
+            }
+        }
+
+        if base.is_dir() {
+            for ext in style_extensions {
+                for candidate in [format!("_index{}", ext), format!("index{}", ext)] {
+                    let path = base.join(candidate);
+                    if path.is_file() {
+                        return Ok(path);
+                    }
+                }
+            }
+        }
+
+        let first_segment = import_path.split('/').next().unwrap_or("");
+        if !import_path.starts_with('.') && import_path.contains('/') && !from_dir.join(first_segment).exists() {
+            return Err(ResolveError::External(import_path.to_string()));
+        }
+        Err(ResolveError::NotFound(format!("no stylesheet matches {}", base.display())))
+    }
+
+    /// Resolves a `/// <reference path="..." />` directive. Unlike module
+    /// specifiers the path is always relative to the referencing file, even
+    /// without a leading `./`.
+    pub fn resolve_reference(&self, from: &Path, reference: &str) -> Result<PathBuf, ResolveError> {
+        let from_dir = from.parent().unwrap_or(Path::new("."));
+        let base = normalize_path(&from_dir.join(reference));
+
+        if base.is_file() {
+            return Ok(base);
+        }
+
+        for ext in [".ts", ".tsx", ".d.ts"] {
+            let mut candidate = base.clone().into_os_string();
+            candidate.push(ext);
+            let candidate = PathBuf::from(candidate);
+            if candidate.is_file() {
+                return Ok(candidate);
+            }
+        }
+
+        Err(ResolveError::NotFound(format!("no file matches {}", base.display())))
+    }
+
+    /// Lists files that a partially known relative specifier could refer to:
+    /// everything under the prefix's directory whose relative path starts with
+    /// the rest of the prefix and ends with the suffix (with or without extension).
+    pub fn expand_partial(&self, from: &Path, partial: &PartialSpecifier) -> Vec<PathBuf> {
+        let Some((dir, name_prefix)) = partial_base(from, partial) else {
+            return vec![];
+        };
+
+        let mut matches = Vec::new();
+        for path in self.walk_files(&dir, None) {
+            let Ok(relative) = path.strip_prefix(&dir) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if !relative.starts_with(name_prefix) {
+                continue;
+            }
+            let without_ext = match relative.rfind('.') {
+                Some(i) if i > relative.rfind('/').map_or(0, |s| s + 1) => &relative[..i],
+                _ => relative.as_str(),
+            };
+            if relative.ends_with(&partial.suffix) || without_ext.ends_with(&partial.suffix) {
+                matches.push(path);
+            }
+        }
+
+        matches.sort();
+        matches
+    }
+
+    /// Directories that a partial specifier or module set is expanded in, so
+    /// files added or removed below them can change what the import matches.
+    pub fn expansion_dirs(&self, from: &Path, import: &ImportInfo) -> Vec<PathBuf> {
+        let from_dir = from.parent().unwrap_or(Path::new("."));
+        match (&import.module_set, &import.partial) {
+            (Some(ModuleSet::Glob { patterns }), _) => patterns
+                .iter()
+                .filter(|pattern| !pattern.starts_with('!'))
+                .flat_map(|pattern| expand_braces(pattern))
+                .filter_map(|pattern| self.glob_base(from_dir, &pattern).map(|(base, _)| base))
+                .collect(),
+            (Some(ModuleSet::Context { directory, .. }), _) => vec![normalize_path(&from_dir.join(directory))],
+            (None, Some(partial)) => partial_base(from, partial).map(|(dir, _)| dir).into_iter().collect(),
+            (None, None) => vec![],
+        }
+    }
+
+    /// Lists the files selected by `import.meta.glob` or `require.context`.
+    pub fn expand_module_set(&self, from: &Path, set: &ModuleSet) -> Vec<PathBuf> {
+        let from_dir = from.parent().unwrap_or(Path::new("."));
+        let mut matches = match set {
+            ModuleSet::Glob { patterns } => {
+                let mut included = Vec::new();
+                let mut excluded = Vec::new();
+                let mut unanchored = Vec::new();
+                for pattern in patterns {
+                    let (negated, pattern) = match pattern.strip_prefix('!') {
+                        Some(rest) => (true, rest),
+                        None => (false, pattern.as_str()),
+                    };
+                    for expanded in expand_braces(pattern) {
+                        if !negated {
+                            included.extend(self.expand_glob(from_dir, &expanded));
+                        } else if expanded.starts_with('.') || expanded.starts_with('/') {
+                            excluded.extend(self.expand_glob(from_dir, &expanded));
+                        } else if let Ok(glob) = glob::Pattern::new(&format!("**/{}", expanded)) {
+                            // Bare negations like `!**/*.spec.ts` apply anywhere
+                            unanchored.push(glob);
+                        }
+                    }
+                }
+                included.retain(|f| {
+                    !excluded.contains(f)
+                        && !unanchored.iter().any(|g| g.matches_path_with(f, GLOB_OPTIONS))
+                });
+                included
+            }
+            ModuleSet::Context { directory, recursive, filter } => {
+                let dir = normalize_path(&from_dir.join(directory));
+                let filter = filter.as_deref().and_then(|f| regex::Regex::new(f).ok());
+                let max_depth = if *recursive { None } else { Some(1) };
+
+                self.walk_files(&dir, max_depth)
+                    .into_iter()
+                    .filter(|path| {
+                        let Some(re) = &filter else { return true };
+                        path.strip_prefix(&dir)
+                            .map(|rel| re.is_match(&format!("./{}", rel.to_string_lossy().replace('\\', "/"))))
+                            .unwrap_or(false)
+                    })
+                    .collect()
+            }
+        };
+
+        matches.sort();
+        matches.dedup();
+        matches
+    }
+
+    /// Matches a single Vite glob. Relative patterns are anchored at the importing
+    /// file's directory and `/`-prefixed ones at the project root.
+    fn expand_glob(&self, from_dir: &Path, pattern: &str) -> Vec<PathBuf> {
+        let Some((base, glob_part)) = self.glob_base(from_dir, pattern) else {
+            return vec![];
+        };
+        let Ok(glob) = glob::Pattern::new(&glob_part) else {
+            return vec![];
+        };
+
+        self.walk_files(&base, None)
+            .into_iter()
+            .filter(|path| {
+                path.strip_prefix(&base)
+                    .map(|rel| glob.matches_path_with(rel, GLOB_OPTIONS))
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    /// Splits a glob into the literal directory it starts from and the
+    /// pattern to match below it.
+    fn glob_base(&self, from_dir: &Path, pattern: &str) -> Option<(PathBuf, String)> {
+        let (anchor, pattern) = match pattern.strip_prefix('/') {
+            Some(rest) => (self.root.as_path(), rest),
+            None if pattern.starts_with('.') => (from_dir, pattern),
+            None => return None,
+        };
+
+        let components: Vec<&str> = pattern.split('/').collect();
+        let literal_len = components
+            .iter()
+            .take_while(|c| !c.contains(['*', '?', '[']))
+            .count()
+            .min(components.len().saturating_sub(1));
+        let base = normalize_path(&anchor.join(components[..literal_len].join("/")));
+        Some((base, components[literal_len..].join("/")))
     }
 
     fn resolve_workspace_package(&self, import_path: &str) -> Option<PathBuf> {
         let (package_name, subpath) = self.parse_package_import(import_path);
+
+        if let Some(package_dir) = self.workspace_packages.get(&package_name) {
+            return self.resolve_in_package(package_dir, &subpath);
+        }
+
         let node_modules_path = self.root.join("node_modules").join(&package_name);
         
         if node_modules_path.exists() {
@@ -98,14 +556,8 @@ impl PathResolver {
             if !real_path.starts_with(&canonical_root) {
                 return None;
             }
-            
-            let target = if subpath.is_empty() {
-                self.resolve_package_entry(&real_path)?
-            } else {
-                real_path.join(&subpath)
-            };
-            
-            let resolved = self.resolve_with_extensions(&target).ok()?;
+
+            let resolved = self.resolve_in_package(&real_path, &subpath)?;
             
             if let Ok(canonical_resolved) = std::fs::canonicalize(&resolved) {
                 if let Ok(relative) = canonical_resolved.strip_prefix(&canonical_root) {
@@ -120,6 +572,25 @@ impl PathResolver {
         None
     }
 
+    /// Resolves `subpath` (empty for the package itself) inside a package
+    /// directory, through its `exports` map when it has one.
+    fn resolve_in_package(&self, package_dir: &Path, subpath: &str) -> Option<PathBuf> {
+        let manifest = read_package_json(package_dir);
+        let target = match manifest.as_ref().and_then(|m| m.get("exports")) {
+            Some(exports) => {
+                let key = if subpath.is_empty() { ".".to_string() } else { format!("./{}", subpath) };
+                package_json::resolve_exports(exports, &key, &self.config.conditions)
+                    .into_iter()
+                    .map(|exported| normalize_path(&package_dir.join(exported)))
+                    .find(|candidate| self.resolve_with_extensions(candidate).is_ok())?
+            }
+            None if subpath.is_empty() => self.resolve_package_entry(package_dir)?,
+            None => package_dir.join(subpath),
+        };
+
+        self.resolve_with_extensions(&target).ok()
+    }
+
     fn parse_package_import(&self, import_path: &str) -> (String, String) {
         let parts: Vec<&str> = import_path.splitn(3, '/').collect();
         
@@ -162,25 +633,14 @@ impl PathResolver {
         Some(package_path.join("index"))
     }
 
-    fn resolve_alias(&self, import_path: &str) -> Option<PathBuf> {
-        for (pattern, targets) in &self.config.paths {
-            let pattern_base = pattern.trim_end_matches('*');
-            
-            if import_path.starts_with(pattern_base) {
-                let suffix = &import_path[pattern_base.len()..];
-                
-                for target in targets {
-                    let target_base = target.trim_end_matches('*');
-                    let base_url = self.config.base_url.as_ref().unwrap_or(&self.root);
-                    let resolved_path = base_url.join(target_base).join(suffix);
-                    
-                    if let Ok(path) = self.resolve_with_extensions(&resolved_path) {
-                        return Some(path);
-                    }
-                }
-            }
-        }
-        None
+    fn resolve_alias(&self, from: &Path, import_path: &str) -> Option<PathBuf> {
+        let nearest = self.tsconfig_for(from);
+        let tsconfig = nearest.as_deref().unwrap_or(&self.config.tsconfig);
+        let paths_root = tsconfig.paths_root().unwrap_or(&self.root);
+
+        tsconfig.path_candidates(import_path)
+            .into_iter()
+            .find_map(|target| self.resolve_with_extensions(&paths_root.join(target)).ok())
     }
 
     fn resolve_with_extensions(&self, base_path: &Path) -> Result<PathBuf, ResolveError> {
@@ -188,13 +648,38 @@ impl PathResolver {
             return Ok(base_path.to_path_buf());
         }
 
+        // ESM TypeScript imports the emitted name (`./x.js`) of a `.ts` source
+        if let Some(sources) = base_path.extension().and_then(|e| e.to_str()).and_then(typescript_sources) {
+            for ext in sources {
+                let source = base_path.with_extension(ext);
+                if source.is_file() {
+                    return Ok(source);
+                }
+            }
+        }
+
         for ext in &self.config.extensions {
-            let with_ext = base_path.with_extension(ext.trim_start_matches('.'));
-            if with_ext.exists() {
+            // Append rather than replace so `./foo.config` finds `foo.config.ts`
+            let mut with_ext = base_path.as_os_str().to_owned();
+            with_ext.push(ext);
+            let with_ext = PathBuf::from(with_ext);
+            if with_ext.is_file() {
                 return Ok(with_ext);
             }
         }
 
+        // A module that only exists per platform (`Button.ios.tsx`, `Button.android.tsx`)
+        for platform in &self.config.platforms {
+            for ext in &self.config.extensions {
+                let mut with_platform = base_path.as_os_str().to_owned();
+                with_platform.push(format!(".{}{}", platform, ext));
+                let with_platform = PathBuf::from(with_platform);
+                if with_platform.is_file() {
+                    return Ok(with_platform);
+                }
+            }
+        }
+
         if base_path.is_dir() {
             for ext in &self.config.extensions {
                 let index = base_path.join(format!("index{}", ext));
@@ -211,24 +696,195 @@ impl PathResolver {
             }
         }
 
-        Err(ResolveError::NotFound(base_path.display().to_string()))
+        Err(ResolveError::NotFound(format!("no file matches {}", base_path.display())))
     }
 
-    fn normalize_path(&self, path: &Path) -> PathBuf {
-        let mut components = Vec::new();
-        for component in path.components() {
-            match component {
-                std::path::Component::ParentDir => {
-                    if !components.is_empty() {
-                        components.pop();
-                    }
-                }
-                std::path::Component::CurDir => {}
-                c => components.push(c),
+    /// Platform variants next to a resolved module, other than the module
+    /// itself: for `Button.tsx` these are e.g. `Button.ios.tsx` and
+    /// `Button.native.tsx`. Bundlers pick one per platform, so an import
+    /// depends on all of them.
+    pub fn platform_variants(&self, resolved: &Path) -> Vec<PathBuf> {
+        if self.config.platforms.is_empty() {
+            return vec![];
+        }
+        let (Some(dir), Some(name)) = (resolved.parent(), resolved.file_name().and_then(|n| n.to_str())) else {
+            return vec![];
+        };
+        let Some(ext) = self.config.extensions.iter()
+            .filter(|ext| name.len() > ext.len() && name.ends_with(ext.as_str()))
+            .max_by_key(|ext| ext.len())
+        else {
+            return vec![];
+        };
+        let stem = &name[..name.len() - ext.len()];
+        let stem = self.config.platforms.iter()
+            .find_map(|platform| stem.strip_suffix(&format!(".{}", platform)))
+            .unwrap_or(stem);
+
+        self.config.platforms.iter()
+            .flat_map(|platform| {
+                self.config.extensions.iter().map(move |ext| dir.join(format!("{}.{}{}", stem, platform, ext)))
+            })
+            .filter(|variant| variant != resolved && variant.is_file())
+            .collect()
+    }
+}
+
+/// The directory a partial specifier's matches are searched in, and the
+/// start of the file names it allows there.
+fn partial_base<'p>(from: &Path, partial: &'p PartialSpecifier) -> Option<(PathBuf, &'p str)> {
+    let prefix = partial.prefix.as_str();
+    if !prefix.starts_with('.') && !prefix.starts_with('/') {
+        return None;
+    }
+
+    let (dir_part, name_prefix) = match prefix.rfind('/') {
+        Some(i) => (&prefix[..=i], &prefix[i + 1..]),
+        None => (prefix, ""),
+    };
+    let from_dir = from.parent().unwrap_or(Path::new("."));
+    Some((normalize_path(&from_dir.join(dir_part)), name_prefix))
+}
+
+/// Lexically resolves `.` and `..` components without touching the filesystem.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                components.pop();
             }
+            std::path::Component::CurDir => {}
+            c => components.push(c),
         }
-        components.iter().collect()
     }
+    components.iter().collect()
+}
+
+/// Whether a bare specifier is shaped like something from outside the
+/// project: an npm package (`react`, `@scope/pkg/sub`, legacy names like
+/// `JSONStream`), a node builtin or a URL.
+fn is_external_specifier(specifier: &str) -> bool {
+    // `node:fs`, `https://...`, `virtual:pwa-register`
+    if specifier.contains(':') {
+        return true;
+    }
+    // Legacy npm names may use any URL-safe character, uppercase included
+    let is_name_part = |part: &str| {
+        !part.is_empty()
+            && part.len() <= 214
+            && !part.starts_with(['.', '_'])
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || "-._~!*'()".contains(c))
+    };
+    let mut segments = specifier.split('/');
+    match segments.next() {
+        Some(scope) if scope.starts_with('@') => {
+            is_name_part(&scope[1..]) && segments.next().is_some_and(is_name_part)
+        }
+        Some(name) => is_name_part(name),
+        None => false,
+    }
+}
+
+/// Node core modules importable without the `node:` prefix. Like Node, a
+/// bare `fs` or `events` means the builtin even if a package of that name
+/// is installed.
+const NODE_BUILTINS: &[&str] = &[
+    "assert", "async_hooks", "buffer", "child_process", "cluster", "console", "constants",
+    "crypto", "dgram", "diagnostics_channel", "dns", "domain", "events", "fs", "http",
+    "http2", "https", "inspector", "module", "net", "os", "path", "perf_hooks", "process",
+    "punycode", "querystring", "readline", "repl", "stream", "string_decoder", "sys",
+    "timers", "tls", "trace_events", "tty", "url", "util", "v8", "vm", "wasi",
+    "worker_threads", "zlib",
+];
+
+/// The npm package a bare specifier imports from: `@scope/pkg` for
+/// `@scope/pkg/sub`, `bootstrap` for Sass's `~bootstrap/scss/grid`. Node
+/// builtins (`fs`, `fs/promises`, `node:fs`) and URLs have none.
+pub fn package_name(specifier: &str) -> Option<String> {
+    let specifier = specifier.strip_prefix('~').unwrap_or(specifier);
+    if specifier.contains(':') || !is_external_specifier(specifier) {
+        return None;
+    }
+    let mut segments = specifier.split('/');
+    let first = segments.next()?;
+    if NODE_BUILTINS.contains(&first) {
+        return None;
+    }
+    if first.starts_with('@') {
+        Some(format!("{}/{}", first, segments.next()?))
+    } else {
+        Some(first.to_string())
+    }
+}
+
+/// Extensions of the TypeScript sources that compile to a file with
+/// extension `emitted`, in the order TypeScript tries them.
+fn typescript_sources(emitted: &str) -> Option<&'static [&'static str]> {
+    match emitted {
+        "js" => Some(&["ts", "tsx", "d.ts"]),
+        "jsx" => Some(&["tsx"]),
+        "mjs" => Some(&["mts", "d.mts"]),
+        "cjs" => Some(&["cts", "d.cts"]),
+        _ => None,
+    }
+}
+
+fn read_package_json(dir: &Path) -> Option<serde_json::Value> {
+    let content = std::fs::read_to_string(dir.join("package.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+const GLOB_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Expands the first `{a,b}` group of a glob (recursively), as fast-glob does.
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let Some(open) = pattern.find('{') else {
+        return vec![pattern.to_string()];
+    };
+
+    // Split the first group at its top-level commas; nested groups are
+    // expanded when the alternatives are recursed into.
+    let mut depth = 0;
+    let mut alternatives = Vec::new();
+    let mut start = open + 1;
+    for (i, c) in pattern.char_indices().skip_while(|(i, _)| *i <= open) {
+        match c {
+            '{' => depth += 1,
+            '}' if depth > 0 => depth -= 1,
+            ',' if depth == 0 => {
+                alternatives.push(&pattern[start..i]);
+                start = i + 1;
+            }
+            '}' => {
+                alternatives.push(&pattern[start..i]);
+                let (head, tail) = (&pattern[..open], &pattern[i + 1..]);
+                return alternatives
+                    .into_iter()
+                    .flat_map(|alt| expand_braces(&format!("{}{}{}", head, alt, tail)))
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+
+    // Unbalanced braces are left for the glob to reject
+    vec![pattern.to_string()]
+}
+
+/// Walks `dir` the way project files are discovered: hidden files and
+/// `.gitignore`d paths are skipped and `node_modules` is never entered.
+pub fn project_walker(dir: &Path) -> WalkBuilder {
+    let mut walker = WalkBuilder::new(dir);
+    walker
+        .hidden(true)
+        .git_ignore(true)
+        .filter_entry(|entry| entry.file_name() != "node_modules");
+    walker
 }
 
 #[cfg(test)]
@@ -267,6 +923,418 @@ mod tests {
         assert!(result.is_ok());
         assert!(result.unwrap().ends_with("index.ts"));
     }
+
+    #[test]
+    fn test_resolve_component_extension() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("Button.vue"), "").unwrap();
+        fs::write(src.join("main.ts"), "").unwrap();
+
+        let mut resolver = PathResolver::new(temp.path().to_path_buf());
+        assert!(resolver.resolve(&src.join("main.ts"), "./Button").is_err());
+
+        resolver.add_extensions(&[".vue".to_string()]);
+        let result = resolver.resolve(&src.join("main.ts"), "./Button").unwrap();
+        assert!(result.ends_with("Button.vue"));
+    }
+
+    #[test]
+    fn test_resolve_asset_import() {
+        let temp = TempDir::new().unwrap();
+        let styles = temp.path().join("styles");
+        fs::create_dir_all(styles.join("theme")).unwrap();
+        fs::write(styles.join("_variables.scss"), "").unwrap();
+        fs::write(styles.join("reset.css"), "").unwrap();
+        fs::write(styles.join("theme").join("_index.scss"), "").unwrap();
+        let main = styles.join("main.scss");
+
+        let resolver = PathResolver::new(temp.path().to_path_buf());
+
+        assert_eq!(resolver.resolve_asset_import(&main, "variables").unwrap(), styles.join("_variables.scss"));
+        assert_eq!(resolver.resolve_asset_import(&main, "./reset.css").unwrap(), styles.join("reset.css"));
+        assert_eq!(resolver.resolve_asset_import(&main, "reset").unwrap(), styles.join("reset.css"));
+        assert_eq!(resolver.resolve_asset_import(&main, "theme").unwrap(), styles.join("theme").join("_index.scss"));
+        assert!(resolver.resolve_asset_import(&main, "missing").is_err());
+    }
+
+    #[test]
+    fn test_resolve_reference_path() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        fs::create_dir_all(src.join("legacy")).unwrap();
+        fs::write(src.join("globals.d.ts"), "").unwrap();
+        fs::write(src.join("legacy").join("shim.ts"), "").unwrap();
+        let from = src.join("legacy").join("app.ts");
+
+        let resolver = PathResolver::new(temp.path().to_path_buf());
+
+        assert_eq!(resolver.resolve_reference(&from, "../globals.d.ts").unwrap(), src.join("globals.d.ts"));
+        assert_eq!(resolver.resolve_reference(&from, "shim.ts").unwrap(), src.join("legacy").join("shim.ts"));
+        assert_eq!(resolver.resolve_reference(&from, "../globals").unwrap(), src.join("globals.d.ts"));
+        assert!(resolver.resolve_reference(&from, "missing.d.ts").is_err());
+    }
+
+    #[test]
+    fn test_resolve_package_exports() {
+        let temp = TempDir::new().unwrap();
+        let ui = temp.path().join("node_modules").join("@acme").join("ui");
+        fs::create_dir_all(ui.join("src").join("icons")).unwrap();
+        fs::write(ui.join("src").join("index.ts"), "").unwrap();
+        fs::write(ui.join("src").join("button.ts"), "").unwrap();
+        fs::write(ui.join("src").join("icons").join("close.tsx"), "").unwrap();
+        fs::write(ui.join("package.json"), r#"{
+            "name": "@acme/ui",
+            "main": "./dist/index.js",
+            "exports": {
+                ".": { "types": "./dist/index.d.ts", "source": "./src/index.ts", "default": "./dist/index.js" },
+                "./icons/*": "./src/icons/*.tsx",
+                "./*": { "source": "./src/*.ts", "default": "./dist/*.js" }
+            }
+        }"#).unwrap();
+        let from = temp.path().join("app").join("main.ts");
+        let ui = fs::canonicalize(&ui).unwrap();
+
+        let mut resolver = PathResolver::new(fs::canonicalize(temp.path()).unwrap());
+
+        assert_eq!(resolver.resolve(&from, "@acme/ui").unwrap(), ui.join("src").join("index.ts"));
+        assert_eq!(resolver.resolve(&from, "@acme/ui/button").unwrap(), ui.join("src").join("button.ts"));
+        assert_eq!(resolver.resolve(&from, "@acme/ui/icons/close").unwrap(), ui.join("src").join("icons").join("close.tsx"));
+
+        // Without the `source` condition the built output is picked, which isn't there
+        resolver.set_conditions(&["import".to_string()]);
+        assert!(resolver.resolve(&from, "@acme/ui").is_err());
+    }
+
+    #[test]
+    fn test_resolve_package_imports() {
+        let temp = TempDir::new().unwrap();
+        let pkg = temp.path().join("packages").join("api");
+        fs::create_dir_all(pkg.join("src").join("db")).unwrap();
+        fs::create_dir_all(pkg.join("src").join("utils")).unwrap();
+        fs::write(pkg.join("src").join("db").join("index.ts"), "").unwrap();
+        fs::write(pkg.join("src").join("utils").join("date.ts"), "").unwrap();
+        fs::write(pkg.join("package.json"), r##"{
+            "name": "api",
+            "imports": {
+                "#db": { "types": "./dist/db.d.ts", "default": "./src/db/index.ts" },
+                "#utils/*": "./src/utils/*"
+            }
+        }"##).unwrap();
+        let from = pkg.join("src").join("routes").join("users.ts");
+
+        let resolver = PathResolver::new(temp.path().to_path_buf());
+
+        assert_eq!(resolver.resolve(&from, "#db").unwrap(), pkg.join("src").join("db").join("index.ts"));
+        assert_eq!(resolver.resolve(&from, "#utils/date").unwrap(), pkg.join("src").join("utils").join("date.ts"));
+        assert!(resolver.resolve(&from, "#missing").is_err());
+    }
+
+    #[test]
+    fn test_resolve_alias_with_nearest_tsconfig() {
+        let temp = TempDir::new().unwrap();
+        let root = fs::canonicalize(temp.path()).unwrap();
+        let web = root.join("packages").join("web");
+        let api = root.join("packages").join("api");
+        fs::create_dir_all(web.join("src").join("lib")).unwrap();
+        fs::create_dir_all(api.join("app").join("lib")).unwrap();
+        fs::write(web.join("src").join("lib").join("db.ts"), "").unwrap();
+        fs::write(api.join("app").join("lib").join("db.ts"), "").unwrap();
+
+        fs::write(root.join("tsconfig.base.json"), r#"{
+            // shared by every package
+            "compilerOptions": { "strict": true, },
+        }"#).unwrap();
+        fs::write(web.join("tsconfig.json"), r#"{
+            "extends": "../../tsconfig.base.json",
+            "compilerOptions": { "paths": { "~/*": ["./src/*"] } }
+        }"#).unwrap();
+        fs::write(api.join("tsconfig.json"), r#"{
+            "extends": "../../tsconfig.base.json",
+            "compilerOptions": { "baseUrl": "./app", "paths": { "~/*": ["*"] } },
+        }"#).unwrap();
+
+        let resolver = PathResolver::new(root.clone());
+
+        let from_web = web.join("src").join("pages").join("home.ts");
+        let from_api = api.join("app").join("routes").join("users.ts");
+        assert_eq!(resolver.resolve(&from_web, "~/lib/db").unwrap(), web.join("src").join("lib").join("db.ts"));
+        assert_eq!(resolver.resolve(&from_api, "~/lib/db").unwrap(), api.join("app").join("lib").join("db.ts"));
+    }
+
+    #[test]
+    fn test_nearest_tsconfig_stops_at_root() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("project");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(temp.path().join("lib")).unwrap();
+        fs::write(temp.path().join("lib").join("db.ts"), "").unwrap();
+        fs::write(temp.path().join("tsconfig.json"), r#"{
+            "compilerOptions": { "paths": { "~/*": ["./*"] } }
+        }"#).unwrap();
+
+        let resolver = PathResolver::new(root.clone());
+        assert!(resolver.resolve(&root.join("src").join("main.ts"), "~/lib/db").is_err());
+    }
+
+    #[test]
+    fn test_resolve_workspace_package_without_node_modules() {
+        let temp = TempDir::new().unwrap();
+        let ui = temp.path().join("packages").join("ui");
+        fs::create_dir_all(ui.join("src")).unwrap();
+        fs::write(ui.join("src").join("index.ts"), "").unwrap();
+        fs::write(ui.join("src").join("button.ts"), "").unwrap();
+        fs::write(ui.join("package.json"), r#"{ "name": "@acme/ui", "main": "./dist/index.js" }"#).unwrap();
+        fs::write(temp.path().join("pnpm-workspace.yaml"), "packages:\n  - 'packages/*'\n").unwrap();
+        let from = temp.path().join("apps").join("web").join("main.ts");
+
+        let mut resolver = PathResolver::new(temp.path().to_path_buf());
+        assert!(resolver.resolve(&from, "@acme/ui").is_err());
+
+        assert_eq!(resolver.load_workspaces(), 1);
+        assert_eq!(resolver.resolve(&from, "@acme/ui").unwrap(), ui.join("src").join("index.ts"));
+        assert_eq!(resolver.resolve(&from, "@acme/ui/src/button").unwrap(), ui.join("src").join("button.ts"));
+    }
+
+    #[test]
+    fn test_resolve_typescript_esm_specifiers() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        for file in ["x.ts", "view.tsx", "esm.mts", "cjs.cts", "ambient.d.ts", "vite.config.ts", "legacy.js"] {
+            fs::write(src.join(file), "").unwrap();
+        }
+        let from = src.join("main.ts");
+
+        let resolver = PathResolver::new(temp.path().to_path_buf());
+
+        assert_eq!(resolver.resolve(&from, "./x.js").unwrap(), src.join("x.ts"));
+        assert_eq!(resolver.resolve(&from, "./view.js").unwrap(), src.join("view.tsx"));
+        assert_eq!(resolver.resolve(&from, "./view.jsx").unwrap(), src.join("view.tsx"));
+        assert_eq!(resolver.resolve(&from, "./esm.mjs").unwrap(), src.join("esm.mts"));
+        assert_eq!(resolver.resolve(&from, "./cjs.cjs").unwrap(), src.join("cjs.cts"));
+        assert_eq!(resolver.resolve(&from, "./esm").unwrap(), src.join("esm.mts"));
+        assert_eq!(resolver.resolve(&from, "./ambient").unwrap(), src.join("ambient.d.ts"));
+        assert_eq!(resolver.resolve(&from, "./ambient.js").unwrap(), src.join("ambient.d.ts"));
+        assert_eq!(resolver.resolve(&from, "./vite.config").unwrap(), src.join("vite.config.ts"));
+        assert_eq!(resolver.resolve(&from, "./legacy.js").unwrap(), src.join("legacy.js"));
+    }
+
+    #[test]
+    fn test_resolve_with_jest_mappings() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let src = root.join("src");
+        fs::create_dir_all(src.join("components")).unwrap();
+        fs::create_dir_all(src.join("utils")).unwrap();
+        fs::create_dir_all(src.join("__mocks__")).unwrap();
+        fs::write(src.join("components").join("Button.tsx"), "").unwrap();
+        fs::write(src.join("utils").join("date.ts"), "").unwrap();
+        fs::write(src.join("__mocks__").join("axios.ts"), "").unwrap();
+        fs::write(root.join("jest.config.json"), r#"{
+            // JSONC is fine here too
+            "moduleNameMapper": { "^@components/(.*)$": "<rootDir>/src/components/$1" },
+            "moduleDirectories": ["node_modules", "src"],
+            "roots": ["<rootDir>/src"],
+        }"#).unwrap();
+        let from = src.join("pages").join("home.ts");
+
+        let mut resolver = PathResolver::new(root.to_path_buf());
+        resolver.set_module_mapper(ModuleMapper::load(root));
+
+        assert_eq!(resolver.resolve(&from, "@components/Button").unwrap(), src.join("components").join("Button.tsx"));
+        assert_eq!(resolver.resolve(&from, "utils/date").unwrap(), src.join("utils").join("date.ts"));
+        assert_eq!(resolver.resolve_manual_mock("axios").unwrap(), src.join("__mocks__").join("axios.ts"));
+        assert!(resolver.resolve_manual_mock("./local").is_none());
+    }
+
+    #[test]
+    fn test_unresolved_imports_tell_project_paths_from_packages() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("tsconfig.json"), r#"{ "compilerOptions": { "paths": { "~/*": ["./src/*"] } } }"#).unwrap();
+        fs::write(root.join("package.json"), r#"{
+            "dependencies": { "react": "18.0.0", "@scope/pkg": "1.0.0", "lodash.debounce": "4.0.0", "@Scope/Pkg": "1.0.0" },
+            "devDependencies": { "_legacy": "1.0.0" }
+        }"#).unwrap();
+        fs::create_dir_all(root.join("node_modules").join("JSONStream")).unwrap();
+        let from = root.join("src").join("app.ts");
+
+        let mut resolver = PathResolver::new(root.to_path_buf());
+        resolver.load_tsconfig(&root.join("tsconfig.json")).unwrap();
+
+        for external in ["react", "@scope/pkg/sub", "lodash.debounce", "JSONStream", "@Scope/Pkg", "_legacy/sub", "node:fs", "fs/promises", "virtual:icons"] {
+            assert!(matches!(resolver.resolve(&from, external), Err(ResolveError::External(_))), "{}", external);
+        }
+        for internal in ["./missing", "~/missing", "~utils/x", "@/utils", "#db", "_private/utils", "utils/y", "Components/Button", "left-pad"] {
+            assert!(matches!(resolver.resolve(&from, internal), Err(ResolveError::NotFound(_))), "{}", internal);
+        }
+        assert!(matches!(resolver.resolve_asset_import(&from, "sass:math"), Err(ResolveError::External(_))));
+        assert!(matches!(resolver.resolve_asset_import(&from, "bootstrap/scss/functions"), Err(ResolveError::External(_))));
+        assert!(matches!(resolver.resolve_asset_import(&from, "variables"), Err(ResolveError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_platform_variants() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        for file in ["Button.tsx", "Button.ios.tsx", "Button.native.tsx", "Icon.ios.tsx", "Icon.android.tsx"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+        let from = root.join("App.tsx");
+
+        let mut resolver = PathResolver::new(root.to_path_buf());
+        assert!(resolver.platform_variants(&root.join("Button.tsx")).is_empty());
+
+        resolver.set_platforms(&["ios".to_string(), "android".to_string(), "native".to_string()]);
+        let button = resolver.resolve(&from, "./Button").unwrap();
+        assert_eq!(button, root.join("Button.tsx"));
+        assert_eq!(resolver.platform_variants(&button), vec![root.join("Button.ios.tsx"), root.join("Button.native.tsx")]);
+
+        // Without a shared file the first platform stands in for the module
+        let icon = resolver.resolve(&from, "./Icon").unwrap();
+        assert_eq!(icon, root.join("Icon.ios.tsx"));
+        assert_eq!(resolver.platform_variants(&icon), vec![root.join("Icon.android.tsx")]);
+    }
+
+    #[test]
+    fn test_expand_partial_specifier() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        let locales = src.join("locales");
+        fs::create_dir_all(locales.join("nested")).unwrap();
+        fs::write(locales.join("en.ts"), "").unwrap();
+        fs::write(locales.join("de.ts"), "").unwrap();
+        fs::write(locales.join("README.md"), "").unwrap();
+        fs::write(locales.join("nested").join("fr.ts"), "").unwrap();
+        fs::write(src.join("i18n.ts"), "").unwrap();
+
+        let resolver = PathResolver::new(temp.path().to_path_buf());
+        let partial = PartialSpecifier { prefix: "./locales/".to_string(), suffix: ".ts".to_string() };
+        let matches = resolver.expand_partial(&src.join("i18n.ts"), &partial);
+
+        assert_eq!(matches, vec![
+            locales.join("de.ts"),
+            locales.join("en.ts"),
+            locales.join("nested").join("fr.ts"),
+        ]);
+
+        let partial = PartialSpecifier { prefix: "./locales/e".to_string(), suffix: String::new() };
+        let matches = resolver.expand_partial(&src.join("i18n.ts"), &partial);
+        assert_eq!(matches, vec![locales.join("en.ts")]);
+    }
+
+    #[test]
+    fn test_expand_partial_skips_ignored_directories() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        for dir in ["handlers", "node_modules/pkg", "dist", ".cache"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            fs::write(root.join(dir).join("a.ts"), "").unwrap();
+        }
+
+        let mut resolver = PathResolver::new(root.to_path_buf());
+        resolver.set_ignore_patterns(&["**/dist/**".to_string()]);
+        let partial = PartialSpecifier { prefix: "./".to_string(), suffix: ".ts".to_string() };
+
+        assert_eq!(resolver.expand_partial(&root.join("index.ts"), &partial), vec![root.join("handlers").join("a.ts")]);
+    }
+
+    #[test]
+    fn test_expand_import_meta_glob() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        let routes = src.join("routes");
+        fs::create_dir_all(routes.join("admin")).unwrap();
+        fs::write(routes.join("home.tsx"), "").unwrap();
+        fs::write(routes.join("about.tsx"), "").unwrap();
+        fs::write(routes.join("home.spec.tsx"), "").unwrap();
+        fs::write(routes.join("admin").join("users.tsx"), "").unwrap();
+        fs::write(src.join("app.ts"), "").unwrap();
+
+        let resolver = PathResolver::new(temp.path().to_path_buf());
+        let from = src.join("app.ts");
+
+        let flat = ModuleSet::Glob { patterns: vec!["./routes/*.tsx".to_string()] };
+        assert_eq!(resolver.expand_module_set(&from, &flat), vec![
+            routes.join("about.tsx"),
+            routes.join("home.spec.tsx"),
+            routes.join("home.tsx"),
+        ]);
+
+        let deep = ModuleSet::Glob {
+            patterns: vec!["/src/routes/**/*.tsx".to_string(), "!**/*.spec.tsx".to_string()],
+        };
+        let deep_matches = resolver.expand_module_set(&from, &deep);
+        assert_eq!(deep_matches.len(), 3);
+        assert!(deep_matches.contains(&routes.join("admin").join("users.tsx")));
+
+        let braces = ModuleSet::Glob { patterns: vec!["./routes/{home,about}.tsx".to_string()] };
+        assert_eq!(resolver.expand_module_set(&from, &braces).len(), 2);
+
+        let nested = ModuleSet::Glob { patterns: vec!["./routes/{about,{home,admin/users}}.tsx".to_string()] };
+        assert_eq!(resolver.expand_module_set(&from, &nested), vec![
+            routes.join("about.tsx"),
+            routes.join("admin").join("users.tsx"),
+            routes.join("home.tsx"),
+        ]);
+    }
+
+    #[test]
+    fn test_expand_glob_skips_node_modules() {
+        let temp = TempDir::new().unwrap();
+        let pkg = temp.path().join("node_modules").join("pkg");
+        fs::create_dir_all(&pkg).unwrap();
+        fs::write(pkg.join("index.ts"), "").unwrap();
+        fs::write(temp.path().join("main.ts"), "").unwrap();
+
+        let resolver = PathResolver::new(temp.path().to_path_buf());
+        let all = ModuleSet::Glob { patterns: vec!["./**/*.ts".to_string()] };
+
+        assert_eq!(
+            resolver.expand_module_set(&temp.path().join("app.ts"), &all),
+            vec![temp.path().join("main.ts")]
+        );
+    }
+
+    #[test]
+    fn test_expand_braces_nested() {
+        assert_eq!(expand_braces("a{b,{c,d}e}f"), vec!["abf", "acef", "adef"]);
+        assert_eq!(expand_braces("{x,y}/{1,2}"), vec!["x/1", "x/2", "y/1", "y/2"]);
+        assert_eq!(expand_braces("open{a,b"), vec!["open{a,b"]);
+    }
+
+    #[test]
+    fn test_expand_require_context() {
+        let temp = TempDir::new().unwrap();
+        let icons = temp.path().join("icons");
+        fs::create_dir_all(icons.join("nested")).unwrap();
+        fs::write(icons.join("a.svg"), "").unwrap();
+        fs::write(icons.join("b.png"), "").unwrap();
+        fs::write(icons.join("nested").join("c.svg"), "").unwrap();
+        let from = temp.path().join("index.js");
+
+        let resolver = PathResolver::new(temp.path().to_path_buf());
+
+        let recursive = ModuleSet::Context {
+            directory: "./icons".to_string(),
+            recursive: true,
+            filter: Some(r"\.svg$".to_string()),
+        };
+        assert_eq!(resolver.expand_module_set(&from, &recursive), vec![
+            icons.join("a.svg"),
+            icons.join("nested").join("c.svg"),
+        ]);
+
+        let flat = ModuleSet::Context {
+            directory: "./icons".to_string(),
+            recursive: false,
+            filter: None,
+        };
+        assert_eq!(resolver.expand_module_set(&from, &flat).len(), 2);
+    }
 }
 
 
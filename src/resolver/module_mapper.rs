@@ -0,0 +1,189 @@
+use std::path::{Path, PathBuf};
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use super::read_package_json;
+use super::tsconfig::strip_jsonc;
+
+/// Resolution rules test runners add on top of Node and TypeScript: jest
+/// `moduleNameMapper`, `moduleDirectories` and `roots`, plus the vitest-style
+/// aliases declared in the `aliases` block of `.scopetestrc.json`.
+#[derive(Debug, Default)]
+pub struct ModuleMapper {
+    rules: Vec<MapperRule>,
+    /// Directories searched for bare specifiers in every ancestor of the
+    /// importing file, like `node_modules` (which itself is skipped)
+    pub module_directories: Vec<String>,
+    /// Jest `roots`; manual mocks of packages live in their `__mocks__` folders
+    pub roots: Vec<PathBuf>,
+}
+
+#[derive(Debug)]
+enum MapperRule {
+    /// `moduleNameMapper` entry: the targets may use `$1`.. for capture groups
+    Pattern { regex: Regex, targets: Vec<String> },
+    /// vitest `resolve.alias` entry: matches `find` itself or `find/...`
+    Alias { find: String, replacement: String },
+}
+
+impl ModuleMapper {
+    /// Reads the jest configuration of the project at `root` from
+    /// `jest.config.json` or, failing that, the `jest` field of package.json.
+    pub fn load(root: &Path) -> Self {
+        let config = std::fs::read_to_string(root.join("jest.config.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Value>(&strip_jsonc(&content)).ok())
+            .or_else(|| read_package_json(root).and_then(|m| m.get("jest").cloned()));
+
+        let mut mapper = ModuleMapper::default();
+        let Some(config) = config else {
+            mapper.roots.push(root.to_path_buf());
+            return mapper;
+        };
+
+        let root_dir = match config.get("rootDir").and_then(|v| v.as_str()) {
+            Some(dir) => root.join(dir),
+            None => root.to_path_buf(),
+        };
+        let expand = |value: &str| value.replace("<rootDir>", &root_dir.to_string_lossy());
+
+        if let Some(mappings) = config.get("moduleNameMapper").and_then(|v| v.as_object()) {
+            for (pattern, targets) in mappings {
+                let Ok(regex) = Regex::new(pattern) else { continue };
+                let targets = string_list(targets).iter().map(|t| expand(t)).collect();
+                mapper.rules.push(MapperRule::Pattern { regex, targets });
+            }
+        }
+
+        mapper.module_directories = config.get("moduleDirectories")
+            .map(string_list)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|d| d != "node_modules")
+            .collect();
+
+        mapper.roots = match config.get("roots") {
+            Some(roots) => string_list(roots).iter().map(|r| PathBuf::from(expand(r))).collect(),
+            None => vec![root_dir.clone()],
+        };
+
+        mapper
+    }
+
+    /// Adds the `aliases` block of `.scopetestrc.json`. Keys starting with `^`
+    /// are regexes like jest's `moduleNameMapper`; other keys are vitest-style
+    /// prefixes. `<rootDir>` and relative targets refer to the project root.
+    pub fn add_aliases(&mut self, root: &Path, aliases: &Map<String, Value>) {
+        let expand = |value: &str| value.replace("<rootDir>", &root.to_string_lossy());
+
+        for (find, targets) in aliases {
+            let targets: Vec<String> = string_list(targets).iter().map(|t| expand(t)).collect();
+            if find.starts_with('^') {
+                if let Ok(regex) = Regex::new(find) {
+                    self.rules.push(MapperRule::Pattern { regex, targets });
+                }
+            } else if let Some(replacement) = targets.into_iter().next() {
+                self.rules.push(MapperRule::Alias { find: find.clone(), replacement });
+            }
+        }
+    }
+
+    /// Targets the first matching rule maps `specifier` to, in the order to try them.
+    pub fn map(&self, specifier: &str) -> Vec<String> {
+        for rule in &self.rules {
+            match rule {
+                MapperRule::Pattern { regex, targets } => {
+                    if let Some(caps) = regex.captures(specifier) {
+                        return targets.iter()
+                            .map(|target| {
+                                let mut expanded = String::new();
+                                caps.expand(&braced_groups(target), &mut expanded);
+                                expanded
+                            })
+                            .collect();
+                    }
+                }
+                MapperRule::Alias { find, replacement } => {
+                    if specifier == find {
+                        return vec![replacement.clone()];
+                    }
+                    if let Some(rest) = specifier.strip_prefix(find.as_str()).and_then(|r| r.strip_prefix('/')) {
+                        return vec![format!("{}/{}", replacement.trim_end_matches('/'), rest)];
+                    }
+                }
+            }
+        }
+        vec![]
+    }
+}
+
+/// A string or an array of strings, as jest accepts for most options.
+fn string_list(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(s) => vec![s.clone()],
+        Value::Array(items) => items.iter().filter_map(|v| v.as_str()).map(String::from).collect(),
+        _ => vec![],
+    }
+}
+
+/// Rewrites `$1` to `${1}` so a group followed by letters (`$1Icon`) isn't
+/// read as a named group by the regex crate.
+fn braced_groups(template: &str) -> String {
+    static GROUP: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    GROUP.get_or_init(|| Regex::new(r"\$(\d+)").unwrap())
+        .replace_all(template, "$${$1}")
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_jest_config_from_package_json() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join("package.json"), r#"{
+            "jest": {
+                "moduleNameMapper": {
+                    "^@components/(.*)$": "<rootDir>/src/components/$1",
+                    "^icons/(.*)$": ["<rootDir>/assets/$1Icon", "<rootDir>/assets/$1"],
+                    "\\.(css|scss)$": "identity-obj-proxy"
+                },
+                "moduleDirectories": ["node_modules", "src"],
+                "roots": ["<rootDir>/src", "<rootDir>/test"]
+            }
+        }"#).unwrap();
+
+        let mapper = ModuleMapper::load(root);
+        let root_str = root.to_string_lossy();
+
+        assert_eq!(mapper.map("@components/Button"), vec![format!("{}/src/components/Button", root_str)]);
+        assert_eq!(
+            mapper.map("icons/close"),
+            vec![format!("{}/assets/closeIcon", root_str), format!("{}/assets/close", root_str)]
+        );
+        assert_eq!(mapper.map("./Button.module.scss"), vec!["identity-obj-proxy"]);
+        assert!(mapper.map("react").is_empty());
+        assert_eq!(mapper.module_directories, vec!["src"]);
+        assert_eq!(mapper.roots, vec![root.join("src"), root.join("test")]);
+    }
+
+    #[test]
+    fn test_aliases_block() {
+        let aliases: Map<String, Value> = serde_json::from_str(r#"{
+            "@": "./src",
+            "^~icons/(.+)$": "<rootDir>/assets/icons/$1.svg"
+        }"#).unwrap();
+
+        let mut mapper = ModuleMapper::default();
+        mapper.add_aliases(Path::new("/repo"), &aliases);
+
+        assert_eq!(mapper.map("@"), vec!["./src"]);
+        assert_eq!(mapper.map("@/utils/date"), vec!["./src/utils/date"]);
+        assert!(mapper.map("@scope/pkg").is_empty());
+        assert_eq!(mapper.map("~icons/close"), vec!["/repo/assets/icons/close.svg"]);
+    }
+}
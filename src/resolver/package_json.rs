@@ -0,0 +1,152 @@
+use serde_json::{Map, Value};
+
+/// Conditions tried when a package.json target is a condition object.
+/// `default` always matches last, whether or not it is listed.
+pub const DEFAULT_CONDITIONS: &[&str] = &["source", "development", "import", "require", "node", "types"];
+
+/// Resolves `subpath` (`.` or `./button`) against a package.json `exports` field
+/// and returns the candidate targets, relative to the package directory.
+///
+/// Condition objects are matched in the order of `conditions` rather than the
+/// object's key order, so a preferred condition such as `source` wins no matter
+/// where the package lists it. Every matching condition contributes a candidate
+/// so the caller can skip targets that don't exist (e.g. unbuilt `dist/` output).
+pub fn resolve_exports(exports: &Value, subpath: &str, conditions: &[String]) -> Vec<String> {
+    match exports.as_object() {
+        Some(map) if map.keys().any(|k| k.starts_with('.')) => resolve_subpath_map(map, subpath, conditions),
+        // `"exports": "./index.js"` or a condition object is shorthand for `{ ".": ... }`
+        _ if subpath == "." => resolve_target(exports, None, conditions),
+        _ => vec![],
+    }
+}
+
+/// Resolves a `#specifier` against a package.json `imports` field. Unlike
+/// `exports`, a target may also be a bare package specifier.
+pub fn resolve_imports(imports: &Value, specifier: &str, conditions: &[String]) -> Vec<String> {
+    match imports.as_object() {
+        Some(map) => resolve_subpath_map(map, specifier, conditions),
+        None => vec![],
+    }
+}
+
+fn resolve_subpath_map(map: &Map<String, Value>, key: &str, conditions: &[String]) -> Vec<String> {
+    if !key.contains('*') {
+        if let Some(target) = map.get(key) {
+            return resolve_target(target, None, conditions);
+        }
+    }
+
+    // Node picks the pattern with the longest prefix before `*`, then the longest key
+    let mut best: Option<(&String, &Value, &str)> = None;
+    for (pattern, target) in map {
+        let Some((prefix, suffix)) = pattern.split_once('*') else {
+            continue;
+        };
+        if key.len() < pattern.len() || !key.starts_with(prefix) || !key.ends_with(suffix) {
+            continue;
+        }
+        let is_better = best.is_none_or(|(current, _, _)| {
+            let current_prefix = current.find('*').unwrap_or(current.len());
+            (prefix.len(), pattern.len()) > (current_prefix, current.len())
+        });
+        if is_better {
+            best = Some((pattern, target, &key[prefix.len()..key.len() - suffix.len()]));
+        }
+    }
+
+    match best {
+        Some((_, target, matched)) => resolve_target(target, Some(matched), conditions),
+        None => vec![],
+    }
+}
+
+fn resolve_target(target: &Value, matched: Option<&str>, conditions: &[String]) -> Vec<String> {
+    match target {
+        Value::String(path) => vec![match matched {
+            Some(matched) => path.replace('*', matched),
+            None => path.clone(),
+        }],
+        Value::Array(targets) => targets.iter().flat_map(|t| resolve_target(t, matched, conditions)).collect(),
+        Value::Object(by_condition) => conditions
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once("default"))
+            .filter_map(|condition| by_condition.get(condition))
+            .flat_map(|t| resolve_target(t, matched, conditions))
+            .collect(),
+        // `null` explicitly hides a subpath
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn conditions(list: &[&str]) -> Vec<String> {
+        list.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn first(candidates: Vec<String>) -> Option<String> {
+        candidates.into_iter().next()
+    }
+
+    #[test]
+    fn test_exports_shorthand_and_conditions() {
+        let defaults = conditions(DEFAULT_CONDITIONS);
+
+        assert_eq!(first(resolve_exports(&json!("./index.js"), ".", &defaults)).as_deref(), Some("./index.js"));
+        assert!(resolve_exports(&json!("./index.js"), "./button", &defaults).is_empty());
+
+        let exports = json!({
+            ".": { "types": "./dist/index.d.ts", "import": "./dist/index.mjs", "source": "./src/index.ts" },
+            "./package.json": "./package.json"
+        });
+        assert_eq!(
+            resolve_exports(&exports, ".", &defaults),
+            vec!["./src/index.ts", "./dist/index.mjs", "./dist/index.d.ts"]
+        );
+        assert_eq!(
+            first(resolve_exports(&exports, ".", &conditions(&["import"]))).as_deref(),
+            Some("./dist/index.mjs")
+        );
+        assert_eq!(first(resolve_exports(&exports, "./package.json", &defaults)).as_deref(), Some("./package.json"));
+        assert!(resolve_exports(&exports, "./missing", &defaults).is_empty());
+    }
+
+    #[test]
+    fn test_exports_subpath_patterns() {
+        let defaults = conditions(DEFAULT_CONDITIONS);
+        let exports = json!({
+            "./*": "./src/*.ts",
+            "./icons/*": { "default": "./src/icons/*.tsx" },
+            "./internal/*": null,
+            "./nested/*": { "node": { "require": "./cjs/*.js" } }
+        });
+
+        assert_eq!(first(resolve_exports(&exports, "./button", &defaults)).as_deref(), Some("./src/button.ts"));
+        assert_eq!(first(resolve_exports(&exports, "./icons/close", &defaults)).as_deref(), Some("./src/icons/close.tsx"));
+        assert!(resolve_exports(&exports, "./internal/secret", &defaults).is_empty());
+        assert_eq!(first(resolve_exports(&exports, "./nested/a", &defaults)).as_deref(), Some("./cjs/a.js"));
+    }
+
+    #[test]
+    fn test_imports_field() {
+        let defaults = conditions(DEFAULT_CONDITIONS);
+        let imports = json!({
+            "#db": { "development": "./src/db/mock.ts", "default": "./src/db/index.ts" },
+            "#utils/*": "./src/utils/*.ts",
+            "#lodash": "lodash"
+        });
+
+        assert_eq!(first(resolve_imports(&imports, "#db", &defaults)).as_deref(), Some("./src/db/mock.ts"));
+        assert_eq!(
+            first(resolve_imports(&imports, "#db", &conditions(&["production"]))).as_deref(),
+            Some("./src/db/index.ts")
+        );
+        assert_eq!(first(resolve_imports(&imports, "#utils/date", &defaults)).as_deref(), Some("./src/utils/date.ts"));
+        assert_eq!(first(resolve_imports(&imports, "#lodash", &defaults)).as_deref(), Some("lodash"));
+        assert!(resolve_imports(&imports, "#missing", &defaults).is_empty());
+    }
+}
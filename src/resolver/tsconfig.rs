@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::{normalize_path, ResolveError};
+
+/// The parts of a tsconfig that affect module resolution, after following
+/// its `extends` chain.
+#[derive(Debug, Clone, Default)]
+pub struct TsConfig {
+    /// `compilerOptions.baseUrl`, made absolute
+    pub base_url: Option<PathBuf>,
+    /// `compilerOptions.paths`
+    pub paths: HashMap<String, Vec<String>>,
+    /// Directory of the config that declared `paths`; targets are relative to
+    /// it when there is no `baseUrl`. `None` if no config in the chain has `paths`.
+    pub paths_base: Option<PathBuf>,
+    /// Config files of the projects listed in `references` (not inherited)
+    pub references: Vec<PathBuf>,
+}
+
+impl TsConfig {
+    /// Loads a tsconfig (JSON with comments and trailing commas) and merges
+    /// everything it `extends`, relative paths and packages alike.
+    pub fn load(path: &Path) -> Result<Self, ResolveError> {
+        Self::load_chain(path, &mut Vec::new())
+    }
+
+    fn load_chain(path: &Path, chain: &mut Vec<PathBuf>) -> Result<Self, ResolveError> {
+        let path = std::fs::canonicalize(path)
+            .map_err(|e| ResolveError::ConfigError(format!("{}: {}", path.display(), e)))?;
+        if chain.contains(&path) {
+            return Err(ResolveError::ConfigError(format!("circular extends in {}", path.display())));
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| ResolveError::ConfigError(format!("{}: {}", path.display(), e)))?;
+        let json: serde_json::Value = serde_json::from_str(&strip_jsonc(&content))
+            .map_err(|e| ResolveError::ConfigError(format!("{}: {}", path.display(), e)))?;
+        let dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+
+        // `extends` may be a list (TS 5.0+); later entries override earlier ones
+        let extends: Vec<&str> = match json.get("extends") {
+            Some(serde_json::Value::String(s)) => vec![s.as_str()],
+            Some(serde_json::Value::Array(items)) => items.iter().filter_map(|v| v.as_str()).collect(),
+            _ => vec![],
+        };
+
+        let mut config = TsConfig::default();
+        chain.push(path.clone());
+        for specifier in extends {
+            let parent_path = resolve_extends(&dir, specifier).ok_or_else(|| {
+                ResolveError::ConfigError(format!("{}: cannot find extended config {}", path.display(), specifier))
+            })?;
+            let parent = Self::load_chain(&parent_path, chain)?;
+            config.inherit(parent);
+        }
+        chain.pop();
+
+        if let Some(options) = json.get("compilerOptions") {
+            if let Some(base_url) = options.get("baseUrl").and_then(|v| v.as_str()) {
+                config.base_url = Some(normalize_path(&dir.join(base_url)));
+            }
+            if let Some(paths) = options.get("paths").and_then(|v| v.as_object()) {
+                config.paths = paths
+                    .iter()
+                    .filter_map(|(pattern, targets)| {
+                        let targets = targets.as_array()?
+                            .iter()
+                            .filter_map(|t| t.as_str().map(String::from))
+                            .collect();
+                        Some((pattern.clone(), targets))
+                    })
+                    .collect();
+                config.paths_base = Some(dir.clone());
+            }
+        }
+
+        if let Some(references) = json.get("references").and_then(|v| v.as_array()) {
+            config.references = references
+                .iter()
+                .filter_map(|r| r.get("path").and_then(|p| p.as_str()))
+                .map(|p| {
+                    let target = normalize_path(&dir.join(p));
+                    if target.is_dir() { target.join("tsconfig.json") } else { target }
+                })
+                .collect();
+        }
+
+        Ok(config)
+    }
+
+    /// Takes over the resolution options `base` defines, as `extends` does.
+    fn inherit(&mut self, base: TsConfig) {
+        if base.base_url.is_some() {
+            self.base_url = base.base_url;
+        }
+        if base.paths_base.is_some() {
+            self.paths = base.paths;
+            self.paths_base = base.paths_base;
+        }
+    }
+
+    /// Substituted `paths` targets for `specifier`, in the order to try them.
+    ///
+    /// Follows TypeScript: a pattern without `*` must match exactly and wins
+    /// outright; otherwise the wildcard pattern with the longest prefix before
+    /// its `*` is used (ties go to the longer suffix, then the pattern that sorts
+    /// first, so the choice never depends on map order). The matched text
+    /// replaces the `*` of each target, wherever it sits (`libs/*/src`).
+    pub fn path_candidates(&self, specifier: &str) -> Vec<String> {
+        if let Some(targets) = self.paths.get(specifier).filter(|_| !specifier.contains('*')) {
+            return targets.clone();
+        }
+
+        let mut best: Option<(&str, &str, &Vec<String>)> = None;
+        for (pattern, targets) in &self.paths {
+            let Some((prefix, suffix)) = pattern.split_once('*') else {
+                continue;
+            };
+            // TypeScript ignores patterns with more than one `*`
+            if suffix.contains('*')
+                || specifier.len() < prefix.len() + suffix.len()
+                || !specifier.starts_with(prefix)
+                || !specifier.ends_with(suffix)
+            {
+                continue;
+            }
+            let is_better = best.is_none_or(|(current, current_suffix, _)| {
+                let current_prefix = current.len() - current_suffix.len() - 1;
+                (prefix.len(), suffix.len(), std::cmp::Reverse(pattern.as_str()))
+                    > (current_prefix, current_suffix.len(), std::cmp::Reverse(current))
+            });
+            if is_better {
+                best = Some((pattern, suffix, targets));
+            }
+        }
+
+        let Some((pattern, suffix, targets)) = best else {
+            return vec![];
+        };
+        let prefix_len = pattern.len() - suffix.len() - 1;
+        let matched = &specifier[prefix_len..specifier.len() - suffix.len()];
+        targets.iter().map(|t| t.replacen('*', matched, 1)).collect()
+    }
+
+    /// Directory `paths` targets are relative to.
+    pub fn paths_root(&self) -> Option<&Path> {
+        self.base_url.as_deref().or(self.paths_base.as_deref())
+    }
+}
+
+/// Finds the file an `extends` specifier points at: a relative path (with or
+/// without `.json`) or a config inside a package in `node_modules`.
+fn resolve_extends(dir: &Path, specifier: &str) -> Option<PathBuf> {
+    let with_json = |path: &Path| -> Option<PathBuf> {
+        if path.is_file() {
+            return Some(path.to_path_buf());
+        }
+        let mut json = path.as_os_str().to_owned();
+        json.push(".json");
+        let json = PathBuf::from(json);
+        if json.is_file() {
+            return Some(json);
+        }
+        let nested = path.join("tsconfig.json");
+        nested.is_file().then_some(nested)
+    };
+
+    if specifier.starts_with('.') || Path::new(specifier).is_absolute() {
+        return with_json(&dir.join(specifier));
+    }
+
+    dir.ancestors()
+        .map(|ancestor| ancestor.join("node_modules").join(specifier))
+        .find_map(|candidate| {
+            // A package root may name its config through the `tsconfig` field
+            if candidate.join("package.json").is_file() {
+                let manifest = std::fs::read_to_string(candidate.join("package.json")).ok()
+                    .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok());
+                if let Some(field) = manifest.as_ref().and_then(|m| m.get("tsconfig")).and_then(|v| v.as_str()) {
+                    let target = candidate.join(field);
+                    if target.is_file() {
+                        return Some(target);
+                    }
+                }
+            }
+            with_json(&candidate)
+        })
+}
+
+/// Turns JSONC (as used by tsconfig files) into plain JSON by dropping
+/// comments and trailing commas outside of strings.
+pub fn strip_jsonc(source: &str) -> String {
+    let chars: Vec<char> = source.chars().collect();
+    let mut out = String::with_capacity(source.len());
+    let mut in_string = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            out.push(c);
+            if c == '\\' {
+                if let Some(&escaped) = chars.get(i + 1) {
+                    out.push(escaped);
+                    i += 1;
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        match (c, chars.get(i + 1)) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+                i += 1;
+            }
+            ('/', Some('/')) => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            ('/', Some('*')) => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+                out.push(' ');
+            }
+            (',', _) => {
+                if !next_token_closes(&chars[i + 1..]) {
+                    out.push(c);
+                }
+                i += 1;
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    out
+}
+
+/// Whether the next significant character (skipping whitespace and comments)
+/// closes an object or array, making a preceding comma a trailing one.
+fn next_token_closes(rest: &[char]) -> bool {
+    let mut i = 0;
+    while i < rest.len() {
+        match (rest[i], rest.get(i + 1)) {
+            (c, _) if c.is_whitespace() => i += 1,
+            ('/', Some('/')) => {
+                while i < rest.len() && rest[i] != '\n' {
+                    i += 1;
+                }
+            }
+            ('/', Some('*')) => {
+                i += 2;
+                while i < rest.len() && !(rest[i] == '*' && rest.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            (c, _) => return c == '}' || c == ']',
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_strip_jsonc() {
+        let source = r#"{
+            // line comment
+            "a": "http://example.com/*not a comment*/", /* block */
+            "b": [1, 2,],
+            "c": { "d": "trailing, \"quoted\"", },
+        }"#;
+        let json: serde_json::Value = serde_json::from_str(&strip_jsonc(source)).unwrap();
+        assert_eq!(json["a"], "http://example.com/*not a comment*/");
+        assert_eq!(json["b"], serde_json::json!([1, 2]));
+        assert_eq!(json["c"]["d"], "trailing, \"quoted\"");
+    }
+
+    fn with_paths(paths: &[(&str, &[&str])]) -> TsConfig {
+        TsConfig {
+            paths: paths.iter()
+                .map(|(p, targets)| (p.to_string(), targets.iter().map(|t| t.to_string()).collect()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_path_candidates_most_specific_match() {
+        let config = with_paths(&[
+            ("@app/*", &["src/*"]),
+            ("@app/core/*", &["packages/core/src/*", "legacy/core/*"]),
+            ("@app/core", &["packages/core/src/index.ts"]),
+            ("*", &["types/*", "node_modules/*"]),
+            ("icons/*.svg", &["assets/icons/*.svg"]),
+            ("lib/*/impl", &["libs/*/src/impl"]),
+        ]);
+
+        assert_eq!(config.path_candidates("@app/core"), vec!["packages/core/src/index.ts"]);
+        assert_eq!(config.path_candidates("@app/core/util"), vec!["packages/core/src/util", "legacy/core/util"]);
+        assert_eq!(config.path_candidates("@app/ui/button"), vec!["src/ui/button"]);
+        assert_eq!(config.path_candidates("icons/close.svg"), vec!["assets/icons/close.svg"]);
+        assert_eq!(config.path_candidates("lib/auth/impl"), vec!["libs/auth/src/impl"]);
+        assert_eq!(config.path_candidates("lodash"), vec!["types/lodash", "node_modules/lodash"]);
+    }
+
+    #[test]
+    fn test_path_candidates_exact_patterns_do_not_match_prefixes() {
+        let config = with_paths(&[("config", &["src/config.ts"]), ("a*", &["x/*"]), ("a*c", &["y/*"])]);
+
+        assert_eq!(config.path_candidates("config"), vec!["src/config.ts"]);
+        assert!(config.path_candidates("config-loader").is_empty());
+        // Equal prefixes: the longer suffix is more specific
+        assert_eq!(config.path_candidates("abc"), vec!["y/b"]);
+    }
+
+    #[test]
+    fn test_extends_chain() {
+        let temp = TempDir::new().unwrap();
+        let root = fs::canonicalize(temp.path()).unwrap();
+        let pkg = root.join("packages").join("app");
+        fs::create_dir_all(&pkg).unwrap();
+        fs::write(root.join("tsconfig.base.json"), r#"{
+            // shared settings
+            "compilerOptions": { "baseUrl": ".", "paths": { "@shared/*": ["shared/*"], }, },
+        }"#).unwrap();
+        fs::write(pkg.join("tsconfig.json"), r#"{ "extends": "../../tsconfig.base" }"#).unwrap();
+        fs::write(pkg.join("tsconfig.paths.json"), r#"{
+            "extends": "./tsconfig.json",
+            "compilerOptions": { "paths": { "@app/*": ["src/*"] } }
+        }"#).unwrap();
+
+        let inherited = TsConfig::load(&pkg.join("tsconfig.json")).unwrap();
+        assert_eq!(inherited.base_url.as_deref(), Some(root.as_path()));
+        assert!(inherited.paths.contains_key("@shared/*"));
+
+        let overridden = TsConfig::load(&pkg.join("tsconfig.paths.json")).unwrap();
+        assert!(!overridden.paths.contains_key("@shared/*"));
+        assert!(overridden.paths.contains_key("@app/*"));
+        assert_eq!(overridden.paths_base.as_deref(), Some(pkg.as_path()));
+        // baseUrl still comes from the base config
+        assert_eq!(overridden.paths_root(), Some(root.as_path()));
+    }
+
+    #[test]
+    fn test_extends_package_and_cycles() {
+        let temp = TempDir::new().unwrap();
+        let root = fs::canonicalize(temp.path()).unwrap();
+        let shared = root.join("node_modules").join("@acme").join("tsconfig");
+        fs::create_dir_all(&shared).unwrap();
+        fs::write(shared.join("package.json"), r#"{ "name": "@acme/tsconfig" }"#).unwrap();
+        fs::write(shared.join("base.json"), r#"{ "compilerOptions": { "baseUrl": "./src" } }"#).unwrap();
+        fs::write(root.join("tsconfig.json"), r#"{
+            "extends": ["@acme/tsconfig/base.json"],
+            "references": [{ "path": "./packages/a" }]
+        }"#).unwrap();
+        fs::create_dir_all(root.join("packages").join("a")).unwrap();
+
+        let config = TsConfig::load(&root.join("tsconfig.json")).unwrap();
+        assert_eq!(config.base_url, Some(shared.join("src")));
+        assert_eq!(config.references, vec![root.join("packages").join("a").join("tsconfig.json")]);
+
+        fs::write(root.join("a.json"), r#"{ "extends": "./b.json" }"#).unwrap();
+        fs::write(root.join("b.json"), r#"{ "extends": "./a.json" }"#).unwrap();
+        assert!(TsConfig::load(&root.join("a.json")).is_err());
+    }
+}
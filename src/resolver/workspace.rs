@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::{read_package_json, GLOB_OPTIONS};
+
+/// Maps every workspace package's `name` to its directory, using the globs in
+/// `pnpm-workspace.yaml` and the root package.json `workspaces` field (npm,
+/// yarn, bun). Nothing has to be installed, so this works on a fresh checkout.
+pub fn discover_packages(root: &Path) -> HashMap<String, PathBuf> {
+    let patterns = workspace_patterns(root);
+    let (excludes, includes): (Vec<&String>, Vec<&String>) = patterns.iter().partition(|p| p.starts_with('!'));
+    let excludes: Vec<glob::Pattern> = excludes
+        .iter()
+        .filter_map(|p| glob::Pattern::new(p.trim_start_matches('!').trim_end_matches('/')).ok())
+        .collect();
+
+    let mut packages = HashMap::new();
+    for pattern in includes {
+        let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+        let manifests = root.join(pattern).join("package.json");
+        let Ok(entries) = glob::glob_with(&manifests.to_string_lossy(), GLOB_OPTIONS) else {
+            continue;
+        };
+
+        for manifest in entries.flatten() {
+            let Some(dir) = manifest.parent() else { continue };
+            let relative = dir.strip_prefix(root).unwrap_or(dir);
+            if relative.components().any(|c| c.as_os_str() == "node_modules")
+                || excludes.iter().any(|e| e.matches_path_with(relative, GLOB_OPTIONS))
+            {
+                continue;
+            }
+            let name = read_package_json(dir)
+                .and_then(|m| m.get("name").and_then(|n| n.as_str()).map(String::from));
+            if let Some(name) = name {
+                packages.entry(name).or_insert_with(|| dir.to_path_buf());
+            }
+        }
+    }
+
+    packages
+}
+
+fn workspace_patterns(root: &Path) -> Vec<String> {
+    let mut patterns = std::fs::read_to_string(root.join("pnpm-workspace.yaml"))
+        .map(|yaml| parse_pnpm_packages(&yaml))
+        .unwrap_or_default();
+
+    if let Some(manifest) = read_package_json(root) {
+        // `"workspaces": [...]` or yarn's `"workspaces": { "packages": [...] }`
+        let workspaces = manifest.get("workspaces")
+            .map(|w| w.get("packages").unwrap_or(w))
+            .and_then(|w| w.as_array());
+        if let Some(workspaces) = workspaces {
+            patterns.extend(workspaces.iter().filter_map(|w| w.as_str()).map(String::from));
+        }
+    }
+
+    patterns
+}
+
+/// Reads the `packages` list of a pnpm-workspace.yaml, in block (`- 'apps/*'`)
+/// or flow (`['apps/*']`) style. The rest of the file is ignored.
+fn parse_pnpm_packages(yaml: &str) -> Vec<String> {
+    let unquote = |item: &str| -> Option<String> {
+        let item = item.split(" #").next().unwrap_or("").trim();
+        let item = item.trim_matches(|c| c == '\'' || c == '"');
+        (!item.is_empty()).then(|| item.to_string())
+    };
+
+    let mut lines = yaml.lines();
+    let Some(value) = lines.by_ref().find_map(|l| l.strip_prefix("packages:")) else {
+        return vec![];
+    };
+
+    let inline = value.trim();
+    if let Some(list) = inline.strip_prefix('[') {
+        return list.trim_end_matches(']').split(',').filter_map(unquote).collect();
+    }
+
+    lines
+        .map(str::trim_end)
+        .filter(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .take_while(|l| l.starts_with(' ') || l.starts_with('\t') || l.starts_with('-'))
+        .filter_map(|l| l.trim_start().strip_prefix('-'))
+        .filter_map(unquote)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_pnpm_packages() {
+        let yaml = "packages:\n  # apps first\n  - 'apps/*'\n  - \"packages/**\" # everything\n  - '!**/test/**'\ncatalog:\n  react: ^18\n";
+        assert_eq!(parse_pnpm_packages(yaml), vec!["apps/*", "packages/**", "!**/test/**"]);
+        assert_eq!(parse_pnpm_packages("packages: ['libs/*', \"tools/cli\"]\n"), vec!["libs/*", "tools/cli"]);
+        assert!(parse_pnpm_packages("catalog:\n  react: ^18\n").is_empty());
+    }
+
+    #[test]
+    fn test_discover_packages() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        for (dir, name) in [
+            ("apps/web", "web"),
+            ("packages/ui", "@acme/ui"),
+            ("packages/test/fixtures", "fixtures"),
+            ("libs/core", "@acme/core"),
+            ("libs/core/node_modules/dep", "dep"),
+        ] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            fs::write(root.join(dir).join("package.json"), format!(r#"{{ "name": "{}" }}"#, name)).unwrap();
+        }
+        fs::write(root.join("pnpm-workspace.yaml"), "packages:\n  - apps/*\n  - packages/**\n  - '!**/test/**'\n").unwrap();
+        fs::write(root.join("package.json"), r#"{ "workspaces": { "packages": ["libs/*"] } }"#).unwrap();
+
+        let packages = discover_packages(root);
+
+        let mut names: Vec<_> = packages.keys().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(names, vec!["@acme/core", "@acme/ui", "web"]);
+        assert_eq!(packages["@acme/ui"], root.join("packages/ui"));
+    }
+}
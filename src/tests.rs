@@ -5,6 +5,7 @@ use tempfile::TempDir;
 use std::fs;
 
 use crate::parser;
+use crate::builder::GraphBuilder;
 use crate::graph::DependencyGraph;
 use crate::config::Config;
 use crate::output::OutputFormatter;
@@ -17,6 +18,157 @@ fn arb_import() -> impl Strategy<Value = String> {
     ]
 }
 
+const MODULES: &[&str] = &["a", "b", "c", "d.test", "e/index", "e", "e/f", "e/g.test"];
+
+/// How a written module refers to its targets
+#[derive(Debug, Clone, Copy)]
+enum Style {
+    SideEffect,
+    ReExportAll,
+    /// `import { m<n> } from`, which may go through the `e/index.ts` barrel
+    Named,
+    /// `export { m<n> } from`, which makes `e/index.ts` a barrel
+    ReExportNamed,
+    /// `import.meta.glob` over `src/e`, ignoring the targets
+    Glob,
+    /// A template literal `import()` into `src/e`, ignoring the targets
+    Partial,
+}
+
+#[derive(Debug, Clone)]
+enum Edit {
+    /// Writes `src/<module>.ts` referring to `(target, symbol)` pairs in the
+    /// given style (and maybe a package). Every module but an index exports
+    /// `m<n>`, named after its own position in `MODULES`.
+    Write { module: usize, imports: Vec<(usize, usize)>, style: Style, package: bool },
+    Delete { module: usize },
+}
+
+fn arb_style() -> impl Strategy<Value = Style> {
+    prop_oneof![
+        Just(Style::SideEffect),
+        Just(Style::ReExportAll),
+        Just(Style::Named),
+        Just(Style::ReExportNamed),
+        Just(Style::Glob),
+        Just(Style::Partial),
+    ]
+}
+
+fn arb_edit() -> impl Strategy<Value = Edit> {
+    let module = 0..MODULES.len();
+    let import = (0..MODULES.len(), 0..MODULES.len());
+    prop_oneof![
+        3 => (module.clone(), prop::collection::vec(import, 0..4), arb_style(), prop::bool::ANY)
+            .prop_map(|(module, imports, style, package)| Edit::Write { module, imports, style, package }),
+        1 => module.prop_map(|module| Edit::Delete { module }),
+    ]
+}
+
+/// The specifier `src/<from>.ts` uses for `src/<to>.ts`.
+fn specifier(from: &str, to: &str) -> String {
+    let from_dir = from.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let to = if from_dir.is_empty() { to.trim_end_matches("/index") } else { to };
+    match to.strip_prefix(from_dir).and_then(|rest| rest.strip_prefix('/')) {
+        Some(rest) if !from_dir.is_empty() => format!("./{}", rest),
+        _ if from_dir.is_empty() => format!("./{}", to),
+        _ => format!("../{}", to),
+    }
+}
+
+fn apply_edit(src: &std::path::Path, edit: &Edit) {
+    match edit {
+        Edit::Write { module, imports, style, package } => {
+            let from = MODULES[*module];
+            let e_dir = if from.starts_with("e/") { "./" } else { "./e/" };
+            let mut code: Vec<String> = match style {
+                Style::Glob => vec![format!("const all = import.meta.glob('{}*.ts');", e_dir)],
+                Style::Partial => vec![format!("const load = (name) => import(`{}${{name}}.ts`);", e_dir)],
+                _ => imports
+                    .iter()
+                    .map(|&(target, symbol)| {
+                        let target = specifier(from, MODULES[target]);
+                        match style {
+                            Style::ReExportAll => format!("export * from '{}';", target),
+                            Style::Named => format!("import {{ m{} }} from '{}';", symbol, target),
+                            Style::ReExportNamed => format!("export {{ m{} }} from '{}';", symbol, target),
+                            _ => format!("import '{}';", target),
+                        }
+                    })
+                    .collect(),
+            };
+            if !from.ends_with("index") {
+                code.push(format!("export const m{} = 1;", module));
+            }
+            if *package {
+                code.push("import React from 'react';".to_string());
+            }
+            let path = src.join(format!("{}.ts", from));
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, code.join("\n")).unwrap();
+        }
+        Edit::Delete { module } => {
+            let _ = fs::remove_file(src.join(format!("{}.ts", MODULES[*module])));
+        }
+    }
+}
+
+type GraphSnapshot = (Vec<(PathBuf, bool)>, Vec<(PathBuf, PathBuf)>, Vec<(PathBuf, String)>);
+
+/// Everything a full build determines, by path rather than by `FileId`.
+fn snapshot(graph: &DependencyGraph) -> GraphSnapshot {
+    let mut files = Vec::new();
+    let mut edges = Vec::new();
+    for id in graph.get_all_files() {
+        let node = graph.get_file_node(id).unwrap();
+        // Every id must lead back to its own path, stored or absolute
+        assert_eq!(graph.get_file_id(&node.path), Some(id));
+        assert_eq!(graph.get_file_id(&graph.get_file_path(id).unwrap()), Some(id));
+        files.push((node.path.clone(), node.is_test));
+        for dep in graph.get_dependencies(id) {
+            edges.push((node.path.clone(), graph.get_relative_path(dep).unwrap().to_path_buf()));
+        }
+    }
+    let unresolved = graph.get_unresolved_imports().into_iter().map(|(path, u)| (path, u.specifier)).collect();
+    files.sort();
+    edges.sort();
+    (files, edges, unresolved)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn prop_incremental_update_matches_full_build(
+        initial in prop::collection::vec(arb_edit(), 0..6),
+        batches in prop::collection::vec(prop::collection::vec(arb_edit(), 1..4), 1..5),
+    ) {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        for edit in &initial {
+            apply_edit(&src, edit);
+        }
+
+        let builder = GraphBuilder::new(temp.path().to_path_buf(), Config::default());
+        let mut graph = builder.build().unwrap();
+
+        for batch in &batches {
+            for edit in batch {
+                apply_edit(&src, edit);
+            }
+            builder.update_incremental(&mut graph).unwrap();
+
+            let rebuilt = GraphBuilder::new(temp.path().to_path_buf(), Config::default()).build().unwrap();
+            prop_assert_eq!(snapshot(&graph), snapshot(&rebuilt));
+
+            let mut restored = DependencyGraph::deserialize(graph.serialize());
+            restored.set_root(temp.path());
+            prop_assert_eq!(snapshot(&restored), snapshot(&rebuilt));
+        }
+    }
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(50))]
 
@@ -28,7 +180,7 @@ proptest! {
         fs::write(&file_path, &code).unwrap();
         let result = parser::parse_file(&file_path);
         prop_assert!(result.is_ok());
-        prop_assert!(result.unwrap().len() >= 1);
+        prop_assert!(!result.unwrap().is_empty());
     }
 
     #[test]
@@ -56,7 +208,7 @@ proptest! {
     #[test]
     fn prop_json_valid(n in 0usize..5) {
         let tests: Vec<PathBuf> = (0..n).map(|i| PathBuf::from(format!("/t{}.spec.ts", i))).collect();
-        let json = OutputFormatter::format_json(&tests, &[], n + 5, 10);
+        let json = OutputFormatter::format_json(&tests, &[], n + 5, 10, &[]);
         prop_assert!(serde_json::from_str::<serde_json::Value>(&json).is_ok());
     }
 
@@ -116,9 +268,10 @@ fn test_jest_escaping() {
 
 #[test]
 fn test_empty_json() {
-    let json = OutputFormatter::format_json(&[], &[], 100, 500);
+    let json = OutputFormatter::format_json(&[], &[], 100, 500, &[]);
     let v: serde_json::Value = serde_json::from_str(&json).unwrap();
     assert_eq!(v["stats"]["affected_tests"], 0);
+    assert!(v.get("parse_errors").is_none());
 }
 
 #[test]